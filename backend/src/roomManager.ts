@@ -1,10 +1,11 @@
 import { Room, Player, ROOM_MAX_PLAYERS } from './types';
 import { v4 as uuidv4 } from 'uuid';
+import { DEFAULT_PASSAGE, PassageInfo } from './scoring';
 
 export class RoomManager {
     private rooms: Map<string, Room> = new Map();
 
-    createRoom(hostId: string, username: string, tournamentId?: string): Room {
+    createRoom(hostId: string, username: string, tournamentId?: string, passage: PassageInfo = DEFAULT_PASSAGE): Room {
         const roomId = uuidv4().slice(0, 6).toUpperCase();
         const newRoom: Room = {
             id: roomId,
@@ -18,6 +19,7 @@ export class RoomManager {
             }],
             status: 'waiting',
             text: "The quick brown fox jumps over the lazy dog. Programming is the art of telling another human what one wants the computer to do.",
+            passage,
             tournamentId
         };
         this.rooms.set(roomId, newRoom);
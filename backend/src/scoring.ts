@@ -0,0 +1,47 @@
+// WPM scoring rules, mirroring `ScoringRule` in contracts/type_arena/src/passage.rs
+// so the server judges finish times the same way the chain scores them.
+
+export type ScoringRule = 'FiveCharWords' | 'Graphemes' | 'Code';
+
+export interface PassageInfo {
+    language: string; // language tag such as 'en', 'ja' or 'rust'
+    scoring: ScoringRule;
+}
+
+export const DEFAULT_PASSAGE: PassageInfo = { language: 'en', scoring: 'FiveCharWords' };
+
+const SCORING_RULES: ScoringRule[] = ['FiveCharWords', 'Graphemes', 'Code'];
+const CHARS_PER_WORD = 5;
+
+export function isPassageInfo(value: unknown): value is PassageInfo {
+    const info = value as PassageInfo | undefined;
+    return typeof info?.language === 'string' && SCORING_RULES.includes(info.scoring);
+}
+
+// Locales to segment a passage with. Tags that are not valid locales, such as
+// the code language 'rust', fall back to the runtime default.
+function segmenterLocales(language: string): string[] {
+    try {
+        return Intl.Segmenter.supportedLocalesOf([language]);
+    } catch {
+        return [];
+    }
+}
+
+// Splits text into grapheme clusters using the passage language.
+function graphemes(text: string, language: string): string[] {
+    const segmenter = new Intl.Segmenter(segmenterLocales(language), { granularity: 'grapheme' });
+    return Array.from(segmenter.segment(text), s => s.segment);
+}
+
+// Number of words the passage is worth under its scoring rule.
+export function wordCount(text: string, passage: PassageInfo): number {
+    switch (passage.scoring) {
+        case 'FiveCharWords':
+            return graphemes(text, passage.language).length / CHARS_PER_WORD;
+        case 'Graphemes':
+            return graphemes(text, passage.language).filter(g => g.trim() !== '').length;
+        case 'Code':
+            return [...text].filter(c => !/\s/u.test(c)).length / CHARS_PER_WORD;
+    }
+}
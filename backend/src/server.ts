@@ -5,6 +5,7 @@ import { RoomManager } from './roomManager';
 import { TournamentManager } from './tournamentManager';
 import { LeaderboardManager } from './leaderboardManager';
 import { COUNTDOWN_TIME } from './types';
+import { DEFAULT_PASSAGE, PassageInfo, isPassageInfo, wordCount } from './scoring';
 
 const app = express();
 const httpServer = createServer(app);
@@ -26,8 +27,8 @@ io.on('connection', (socket) => {
     socket.emit('leaderboard_update', leaderboardManager.getTopScores());
 
     // --- Normal Room Events ---
-    socket.on('create_room', ({ username }: { username: string }) => {
-        const room = roomManager.createRoom(socket.id, username);
+    socket.on('create_room', ({ username, passage }: { username: string, passage?: PassageInfo }) => {
+        const room = roomManager.createRoom(socket.id, username, undefined, isPassageInfo(passage) ? passage : DEFAULT_PASSAGE);
         socket.join(room.id);
         socket.emit('room_created', room);
         console.log(`Room ${room.id} created by ${username}`);
@@ -76,13 +77,13 @@ io.on('connection', (socket) => {
                 // 2. Finish Time Consistency
                 if (progress >= 100 && !player.finished) {
                     const timeTaken = (Date.now() - (room.startTime || 0)) / 1000 / 60; // minutes
-                    const wordCount = room.text.length / 5;
-                    const calculatedWpm = wordCount / timeTaken;
+                    // Score the passage with its own rule, as type_arena does, so CJK and code races are judged fairly.
+                    const calculatedWpm = wordCount(room.text, room.passage) / timeTaken;
 
                     // If calculated WPM differs significantly from reported WPM or is impossibly high
                     // Calculated WPM might be slightly off due to network latency, so allow margin
                     if (calculatedWpm > 350) {
-                        console.log(`CHEAT DETECTED: Player ${player.username} finished impossibly fast. Calc WPM: ${calculatedWpm}`);
+                        console.log(`CHEAT DETECTED: Player ${player.username} finished impossibly fast. Calc WPM: ${calculatedWpm} (${room.passage.language}, ${room.passage.scoring})`);
                         return;
                     }
 
@@ -1,3 +1,5 @@
+import { PassageInfo } from './scoring';
+
 export interface Player {
     id: string;
     username: string;
@@ -22,6 +24,7 @@ export interface Room {
     players: Player[];
     status: 'waiting' | 'countdown' | 'racing' | 'finished';
     text: string;
+    passage: PassageInfo;
     startTime?: number;
     tournamentId?: string;
 }
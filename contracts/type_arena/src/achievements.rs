@@ -0,0 +1,80 @@
+use crate::state::PlayerStats;
+use serde::{Deserialize, Serialize};
+
+/// Races a player must finish to unlock `Achievement::Centurion`.
+pub const CENTURION_RACES: u32 = 100;
+
+/// WPM a single result must reach to unlock `Achievement::SpeedDemon`.
+pub const SPEED_DEMON_WPM: u32 = 120;
+
+/// Consecutive wins needed to unlock `Achievement::Unstoppable`.
+pub const UNSTOPPABLE_STREAK: u32 = 10;
+
+/// Milestones a player can unlock by racing.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+    async_graphql::Enum,
+)]
+pub enum Achievement {
+    /// Won a race for the first time.
+    FirstWin,
+    /// Finished 100 races.
+    Centurion,
+    /// Submitted a result of 120 WPM or more.
+    SpeedDemon,
+    /// Won 10 races in a row.
+    Unstoppable,
+    /// Won a tournament.
+    TournamentChampion,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::FirstWin,
+        Achievement::Centurion,
+        Achievement::SpeedDemon,
+        Achievement::Unstoppable,
+        Achievement::TournamentChampion,
+    ];
+
+    /// Human readable badge name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "First Win",
+            Achievement::Centurion => "Centurion",
+            Achievement::SpeedDemon => "Speed Demon",
+            Achievement::Unstoppable => "Unstoppable",
+            Achievement::TournamentChampion => "Tournament Champion",
+        }
+    }
+
+    /// Whether the given stats satisfy this milestone.
+    pub fn is_reached(&self, stats: &PlayerStats) -> bool {
+        match self {
+            Achievement::FirstWin => stats.wins >= 1,
+            Achievement::Centurion => stats.total_races >= CENTURION_RACES,
+            Achievement::SpeedDemon => stats.best_wpm >= SPEED_DEMON_WPM,
+            Achievement::Unstoppable => stats.best_win_streak >= UNSTOPPABLE_STREAK,
+            Achievement::TournamentChampion => stats.tournaments_won >= 1,
+        }
+    }
+}
+
+/// Returns the milestones reached by `stats` that are not in `unlocked` yet.
+pub fn newly_reached(stats: &PlayerStats, unlocked: &[Achievement]) -> Vec<Achievement> {
+    Achievement::ALL
+        .iter()
+        .copied()
+        .filter(|achievement| !unlocked.contains(achievement) && achievement.is_reached(stats))
+        .collect()
+}
+
+/// A non-transferable badge minted for an unlocked achievement.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct Badge {
+    pub token_id: u64,
+    pub owner: String,
+    pub achievement: Achievement,
+    pub name: String,
+    pub minted_at: u64,
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use crate::state::PlayerResult;
+
+/// How long spectator markets stay open at most. Betting normally locks earlier,
+/// when the race starts.
+pub const MARKET_DURATION_MINUTES: u64 = 60;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum RaceBetKind {
+    /// Will `player` win the race?
+    Winner,
+    /// Will `player` finish ahead of `opponent`?
+    HeadToHead,
+    /// Will `player` finish above `wpm`?
+    OverUnder,
+}
+
+/// The question a spectator market is opened on.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject, async_graphql::InputObject)]
+#[graphql(input_name = "RaceBetInput")]
+pub struct RaceBet {
+    pub kind: RaceBetKind,
+    pub player: String,
+    /// Required for `HeadToHead`.
+    pub opponent: Option<String>,
+    /// Required for `OverUnder`.
+    pub wpm: Option<u32>,
+}
+
+/// What happens to a room's spectator market when the room finishes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketSettlement {
+    /// Betting locked when the race started, so the bet is reported to the oracle.
+    Resolve { market_id: u64, came_true: bool },
+    /// The race never started and the market is still open, so every bet is refunded.
+    Cancel { market_id: u64 },
+}
+
+/// A spectator market opened for a room.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct RaceMarket {
+    pub market_id: u64,
+    pub bet: RaceBet,
+}
+
+impl RaceBet {
+    pub fn is_valid(&self) -> bool {
+        match self.kind {
+            RaceBetKind::Winner => true,
+            RaceBetKind::HeadToHead => self.opponent.as_ref().is_some_and(|opponent| *opponent != self.player),
+            RaceBetKind::OverUnder => self.wpm.is_some(),
+        }
+    }
+
+    pub fn question(&self, room_id: &str) -> String {
+        match self.kind {
+            RaceBetKind::Winner => format!("Will {} win room {}?", self.player, room_id),
+            RaceBetKind::HeadToHead => format!(
+                "Will {} finish ahead of {} in room {}?",
+                self.player,
+                self.opponent.as_deref().unwrap_or_default(),
+                room_id
+            ),
+            RaceBetKind::OverUnder => format!(
+                "Will {} type over {} WPM in room {}?",
+                self.player,
+                self.wpm.unwrap_or_default(),
+                room_id
+            ),
+        }
+    }
+
+    /// Whether the bet came true, given results from first to last place.
+    /// A player without a result never wins a bet.
+    pub fn outcome(&self, ranked: &[&PlayerResult]) -> bool {
+        let place = |player: &str| ranked.iter().position(|result| result.address == player);
+        match self.kind {
+            RaceBetKind::Winner => place(&self.player) == Some(0),
+            RaceBetKind::HeadToHead => match (place(&self.player), self.opponent.as_deref().and_then(place)) {
+                (Some(player), Some(opponent)) => player < opponent,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+            RaceBetKind::OverUnder => ranked
+                .iter()
+                .find(|result| result.address == self.player)
+                .is_some_and(|result| result.wpm > self.wpm.unwrap_or_default()),
+        }
+    }
+}
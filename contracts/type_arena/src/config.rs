@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Game rules set at instantiation and changeable by admins via `UpdateConfig`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject, async_graphql::InputObject)]
+#[graphql(input_name = "GameConfigInput")]
+pub struct GameConfig {
+    /// Results above this WPM are rejected as implausible.
+    pub max_wpm: u32,
+    /// Player capacity given to new rooms. `0` means unlimited.
+    pub default_room_size: u32,
+    /// Time after a room's start during which results are accepted. `0` means no deadline.
+    pub race_deadline_micros: u64,
+    /// Passage languages rooms may use. Empty means any language.
+    pub allowed_languages: Vec<String>,
+    /// Whether results and relay segments are rejected without a keystroke proof.
+    pub require_keystroke_proofs: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            max_wpm: 300,
+            default_room_size: 0,
+            race_deadline_micros: 0,
+            allowed_languages: vec![],
+            require_keystroke_proofs: false,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn is_plausible_wpm(&self, wpm: u32) -> bool {
+        wpm <= self.max_wpm
+    }
+
+    pub fn allows_language(&self, language: &str) -> bool {
+        self.allowed_languages.is_empty() || self.allowed_languages.iter().any(|allowed| allowed == language)
+    }
+
+    /// Whether a result submitted at `now` still counts for a room started at `start_time`.
+    pub fn within_deadline(&self, start_time: u64, now: u64) -> bool {
+        self.race_deadline_micros == 0 || now <= start_time.saturating_add(self.race_deadline_micros)
+    }
+}
@@ -1,14 +1,18 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
-use self::state::TypeArenaState;
 use linera_sdk::{
     Contract, ContractRuntime,
     views::{RootView, View},
-    linera_base_types::{WithContractAbi, StreamName},
+    linera_base_types::{Account, AccountOwner, Amount, ChainId, WithContractAbi, StreamName},
+};
+use abi::{MarketType, Outcome};
+use market::{MarketOperation, MarketResponse};
+use oracle::{OracleOperation, OracleResponse};
+use token::{TokenOperation, TokenResponse};
+use type_arena::{
+    relay::RelaySetup, betting::{self, MarketSettlement}, versioning, Achievement, Friend, GameConfig, PracticePassage, RaceBet, RaceMarket, ModerationKind, ModerationRecord, TypeArenaAbi, TypeArenaParameters, TypeArenaState,
+    Operation, Message, RaceRecord, RewardPayout, RewardReason, Submission, TypeArenaEvent,
 };
-use type_arena::{TypeArenaAbi, Operation, Message, TypeArenaEvent};
 use serde::{Deserialize, Serialize};
 
 linera_sdk::contract!(TypeArena);
@@ -24,8 +28,9 @@ impl WithContractAbi for TypeArena {
 
 impl Contract for TypeArena {
     type Message = Message;
-    type Parameters = ();
-    type InstantiationArgument = ();
+    type Parameters = TypeArenaParameters;
+    /// Initial game rules. `None` uses `GameConfig::default()`.
+    type InstantiationArgument = Option<GameConfig>;
     type EventValue = TypeArenaEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
@@ -35,19 +40,25 @@ impl Contract for TypeArena {
         TypeArena { state, runtime }
     }
 
-    async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
-        // Init
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        // Validate parameters.
+        self.runtime.application_parameters();
+        self.state.config.set(argument.unwrap_or_default());
+        self.state.schema_version.set(versioning::SCHEMA_VERSION);
+        let now = self.runtime.system_time().micros();
+        self.state.start_first_season(now);
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         match operation {
-            Operation::CreateRoom { room_id, text } => {
+            Operation::CreateRoom { room_id, text, passage } => {
                 let signer = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
                 let start_time = self.runtime.system_time().micros(); 
                 self.state.create_room(
                     room_id.clone(),
                     signer,
                     text,
+                    passage,
                     start_time,
                 ).await.expect("Failed to create room");
                 self.runtime.emit(
@@ -55,39 +66,275 @@ impl Contract for TypeArena {
                     &TypeArenaEvent::RoomCreated { room_id }
                 );
             }
-            Operation::JoinRoom { room_id, host_chain_id } => {
+            Operation::CreateRelayRoom { room_id, text, passage, team_count, team_size } => {
+                let signer = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let start_time = self.runtime.system_time().micros();
+                self.state.create_relay_room(
+                    room_id.clone(),
+                    signer,
+                    text,
+                    passage,
+                    start_time,
+                    RelaySetup { team_count, team_size },
+                ).await.expect("Failed to create relay room");
+                self.runtime.emit(
+                    StreamName::from("events"),
+                    &TypeArenaEvent::RoomCreated { room_id }
+                );
+            }
+            Operation::JoinTeam { room_id, team, host_chain_id } => {
                 let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
                 if host_chain_id == self.runtime.chain_id() {
-                    self.state.join_room(room_id.clone(), player.clone()).await.expect("Failed to join room locally");
-                    self.runtime.emit(
-                        StreamName::from("events"),
-                        &TypeArenaEvent::PlayerJoined { room_id, player }
-                    );
+                    self.join_team(room_id, team, player).await;
+                } else {
+                    let message = Message::JoinTeam { room_id, team, player };
+                    self.runtime.send_message(host_chain_id, message);
+                }
+            }
+            Operation::SubmitSegment { room_id, submission, host_chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                if host_chain_id == self.runtime.chain_id() {
+                    self.submit_segment(room_id, player, submission).await;
                 } else {
-                    let message = Message::JoinRoom { room_id, player };
+                    let message = Message::SubmitSegment { room_id, player, submission };
                     self.runtime.send_message(host_chain_id, message);
                 }
             }
-            Operation::SubmitResult { room_id, wpm, time_ms, host_chain_id } => {
+            Operation::JoinRoom { room_id, host_chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.join_room(room_id, host_chain_id, player).await;
+            }
+            Operation::SubmitResult { room_id, submission, accuracy, host_chain_id } => {
                 let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
                 if host_chain_id == self.runtime.chain_id() {
-                    self.state.submit_result(room_id.clone(), player.clone(), wpm, time_ms).await.expect("Failed to submit result locally");
+                    let now = self.runtime.system_time().micros();
+                    let wpm = submission.wpm;
+                    let unlocked = self.state.submit_result(room_id.clone(), player.clone(), submission, accuracy, now).await.expect("Failed to submit result locally");
+                    self.note_player_chain(&room_id, &player).await;
                     self.runtime.emit(
                         StreamName::from("events"),
-                        &TypeArenaEvent::ResultSubmitted { room_id, player, wpm }
+                        &TypeArenaEvent::ResultSubmitted { room_id, player: player.clone(), wpm }
                     );
+                    self.emit_achievements(&player, unlocked);
                 } else {
-                    let message = Message::SubmitResult { room_id, player, wpm, time_ms };
+                    let message = Message::SubmitResult { room_id, player, submission, accuracy };
                     self.runtime.send_message(host_chain_id, message);
                 }
             }
             Operation::FinishRoom { room_id } => {
-                self.state.finish_room(room_id.clone()).await.expect("Failed to finish room");
+                let signer = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let admin = self.admin().is_some();
+                let now = self.runtime.system_time().micros();
+                let finished = self.state.finish_room(room_id.clone(), &signer, admin, now).await.expect("Failed to finish room");
                  self.runtime.emit(
                     StreamName::from("events"),
-                    &TypeArenaEvent::RoomFinished { room_id }
+                    &TypeArenaEvent::RoomFinished { room_id: room_id.clone(), team_standings: finished.team_standings }
+                );
+                for (player, achievement) in finished.unlocked {
+                    self.emit_achievements(&player, vec![achievement]);
+                }
+                for (chain_id, record) in finished.records {
+                    if finished.rewarded {
+                        self.pay_reward(&record.player, chain_id, RewardReason::RacePlacement, room_id.clone(), record.placement).await;
+                    }
+                    self.confirm_race(chain_id, record).await;
+                }
+                match finished.market {
+                    Some(MarketSettlement::Resolve { market_id, came_true }) => {
+                        let oracle_app = self.runtime.application_parameters().oracle_app.expect("No oracle application configured");
+                        let outcome = if came_true { Outcome::Up } else { Outcome::Down };
+                        let _: OracleResponse = self.runtime.call_application(
+                            true,
+                            oracle_app,
+                            &OracleOperation::ReportOutcome { market_id, outcome },
+                        );
+                    }
+                    Some(MarketSettlement::Cancel { market_id }) => self.cancel_market(market_id),
+                    None => {}
+                }
+            }
+            Operation::StartCountdown { room_id, bet } => {
+                let signer = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.state.start_countdown(room_id.clone(), &signer, bet.as_ref()).await.expect("Failed to start countdown");
+                let market_id = match bet {
+                    Some(bet) => Some(self.open_market(room_id.clone(), bet).await),
+                    None => None,
+                };
+                self.runtime.emit(
+                    StreamName::from("events"),
+                    &TypeArenaEvent::CountdownStarted { room_id, market_id }
                 );
             }
+            Operation::StartRace { room_id } => {
+                let signer = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let now = self.runtime.system_time().micros();
+                let market_id = self.state.start_race(room_id.clone(), &signer, now).await.expect("Failed to start race");
+                if let Some(market_id) = market_id {
+                    let market_app = self.runtime.application_parameters().market_app.expect("No market application configured");
+                    let _: MarketResponse = self.runtime.call_application(
+                        true,
+                        market_app,
+                        &MarketOperation::LockMarket { market_id },
+                    );
+                }
+                self.runtime.emit(
+                    StreamName::from("events"),
+                    &TypeArenaEvent::RaceStarted { room_id }
+                );
+            }
+            Operation::CloseSeason => {
+                let now = self.runtime.system_time().micros();
+                // Admins may end a season early; anyone may close it once it has run its course.
+                let admin = self.admin();
+                let archive = self.state.close_season(now, admin.is_some()).await.expect("Failed to close season");
+                if let Some(admin) = admin {
+                    let record = self.state.log_moderation(
+                        admin,
+                        ModerationKind::CloseSeason,
+                        None,
+                        None,
+                        format!("Closed season {}", archive.season),
+                        now,
+                    );
+                    self.emit_moderation(record);
+                }
+                for standing in &archive.standings {
+                    let chain_id = self.state.player_chain(&standing.player).await.expect("Failed to read player chain");
+                    self.pay_reward(&standing.player, chain_id, RewardReason::SeasonStanding, archive.season.to_string(), standing.rank).await;
+                }
+                self.runtime.emit(
+                    StreamName::from("events"),
+                    &TypeArenaEvent::SeasonClosed { season: archive.season, standings: archive.standings }
+                );
+            }
+            Operation::BanPlayer { player, reason } => {
+                let admin = self.admin().expect("Only admins can ban players");
+                let now = self.runtime.system_time().micros();
+                let record = self.state.ban_player(admin, player, reason, now).await.expect("Failed to ban player");
+                self.emit_moderation(record);
+            }
+            Operation::UnbanPlayer { player, reason } => {
+                let admin = self.admin().expect("Only admins can unban players");
+                let now = self.runtime.system_time().micros();
+                let record = self.state.unban_player(admin, player, reason, now).await.expect("Failed to unban player");
+                self.emit_moderation(record);
+            }
+            Operation::InvalidateResult { room_id, player, reason } => {
+                let admin = self.admin().expect("Only admins can invalidate results");
+                let now = self.runtime.system_time().micros();
+                let invalidated = self.state.invalidate_result(admin, room_id.clone(), player.clone(), reason, now).await.expect("Failed to invalidate result");
+                self.emit_moderation(invalidated.record);
+                if invalidated.confirmed {
+                    self.void_race(invalidated.chain_id, room_id, player).await;
+                }
+            }
+            Operation::UpdateConfig { config } => {
+                let admin = self.admin().expect("Only admins can update the game config");
+                let now = self.runtime.system_time().micros();
+                let record = self.state.update_config(admin, config, now);
+                self.emit_moderation(record);
+            }
+            Operation::DeleteRoom { room_id, reason } => {
+                let admin = self.admin().expect("Only admins can delete rooms");
+                let now = self.runtime.system_time().micros();
+                // Bets on a room that will never finish are refunded.
+                let market_id = self
+                    .state
+                    .room(&room_id)
+                    .await
+                    .expect("Failed to read room")
+                    .filter(|room| !room.is_finished)
+                    .and_then(|room| room.market)
+                    .map(|market| market.market_id);
+                if let Some(market_id) = market_id {
+                    self.cancel_market(market_id);
+                }
+                let record = self.state.delete_room(admin, room_id, reason, now).await.expect("Failed to delete room");
+                self.emit_moderation(record);
+            }
+            Operation::MigrateState { batch_size } => {
+                let admin = self.admin().expect("Only admins can migrate state");
+                let report = self.state.migrate(batch_size).await.expect("Failed to migrate state");
+                let now = self.runtime.system_time().micros();
+                let reason = format!(
+                    "Migrated {} rooms, {} tournaments and {} player stats{}",
+                    report.rooms,
+                    report.tournaments,
+                    report.player_stats,
+                    if report.complete { "; migration complete" } else { "" },
+                );
+                let record = self.state.log_moderation(admin, ModerationKind::MigrateState, None, None, reason, now);
+                self.emit_moderation(record);
+            }
+            Operation::AwardTournament { tournament_id, winner } => {
+                let admin = self.admin().expect("Only admins can award tournaments");
+                let unlocked = self
+                    .state
+                    .award_tournament(tournament_id.clone(), winner.clone(), admin.clone())
+                    .await
+                    .expect("Failed to award tournament");
+                let now = self.runtime.system_time().micros();
+                let reason = format!("Awarded tournament {} to {}", tournament_id, winner);
+                let record = self.state.log_moderation(admin, ModerationKind::AwardTournament, Some(winner.clone()), None, reason, now);
+                self.emit_moderation(record);
+                self.emit_achievements(&winner, unlocked);
+                let chain_id = self.state.player_chain(&winner).await.expect("Failed to read player chain");
+                self.pay_reward(&winner, chain_id, RewardReason::TournamentWin, tournament_id, 1).await;
+            }
+            Operation::AddFriend { player: friend, chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.state.add_friend(player, Friend { player: friend, chain_id }).await.expect("Failed to add friend");
+            }
+            Operation::RemoveFriend { player: friend } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.state.remove_friend(player, friend).await.expect("Failed to remove friend");
+            }
+            Operation::InviteToRoom { friend, room_id, host_chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let friend = self.state.friend(&player, &friend).await.expect("Can only invite friends");
+                let message = Message::InviteToRoom { room_id, host_chain_id, from: player, to: friend.player };
+                if friend.chain_id == self.runtime.chain_id() {
+                    self.execute_message(message).await;
+                } else {
+                    self.runtime.send_message(friend.chain_id, message);
+                }
+            }
+            Operation::AcceptInvite { invite_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let invite = self.state.take_invite(invite_id, &player).await.expect("Failed to accept invite");
+                self.join_room(invite.room_id, invite.host_chain_id, player).await;
+            }
+            Operation::DeclineInvite { invite_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.state.take_invite(invite_id, &player).await.expect("Failed to decline invite");
+            }
+            Operation::RecordPractice { keys, bigrams } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                self.state.record_practice(player, keys, bigrams).await.expect("Failed to record practice");
+            }
+            Operation::AddLibraryPassage { text } => {
+                self.admin().expect("Only admins can add library passages");
+                self.state.add_library_passage(text).expect("Failed to add library passage");
+            }
+            Operation::RequestPracticePassage { lobby_chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                let focus = self.state.focus_bigrams(&player).await.expect("Failed to read practice stats");
+                let message = Message::RequestPracticePassage { player, focus };
+                if lobby_chain_id == self.runtime.chain_id() {
+                    self.execute_message(message).await;
+                } else {
+                    self.runtime.send_message(lobby_chain_id, message);
+                }
+            }
+            Operation::MintBadge { achievement, host_chain_id } => {
+                let player = self.runtime.authenticated_signer().map(|s| s.to_string()).unwrap_or_default();
+                if host_chain_id == self.runtime.chain_id() {
+                    self.mint_badge(player, achievement).await;
+                } else {
+                    let message = Message::MintBadge { player, achievement };
+                    self.runtime.send_message(host_chain_id, message);
+                }
+            }
         }
     }
 
@@ -97,18 +344,69 @@ impl Contract for TypeArena {
                 // In a real app, verify 'player' against message sender authentication if needed.
                 // For now, we trust the message content for simplicity or assume signed messages.
                  self.state.join_room(room_id.clone(), player.clone()).await.expect("Failed to process JoinRoom message");
+                 self.note_player_chain(&room_id, &player).await;
                  self.runtime.emit(
                     StreamName::from("events"),
                     &TypeArenaEvent::PlayerJoined { room_id, player }
                 );
             }
-            Message::SubmitResult { room_id, player, wpm, time_ms } => {
-                 self.state.submit_result(room_id.clone(), player.clone(), wpm, time_ms).await.expect("Failed to process SubmitResult message");
+            Message::SubmitResult { room_id, player, submission, accuracy } => {
+                 let now = self.runtime.system_time().micros();
+                 let wpm = submission.wpm;
+                 let unlocked = self.state.submit_result(room_id.clone(), player.clone(), submission, accuracy, now).await.expect("Failed to process SubmitResult message");
+                 self.note_player_chain(&room_id, &player).await;
                  self.runtime.emit(
                     StreamName::from("events"),
-                    &TypeArenaEvent::ResultSubmitted { room_id, player, wpm }
+                    &TypeArenaEvent::ResultSubmitted { room_id, player: player.clone(), wpm }
+                );
+                 self.emit_achievements(&player, unlocked);
+            }
+            Message::JoinTeam { room_id, team, player } => {
+                self.join_team(room_id, team, player).await;
+            }
+            Message::SubmitSegment { room_id, player, submission } => {
+                self.submit_segment(room_id, player, submission).await;
+            }
+            Message::MintBadge { player, achievement } => {
+                self.mint_badge(player, achievement).await;
+            }
+            Message::InviteToRoom { room_id, host_chain_id, from, to } => {
+                let now = self.runtime.system_time().micros();
+                let invite = self.state.receive_invite(from, to, room_id, host_chain_id, now).expect("Failed to store invite");
+                self.runtime.emit(
+                    StreamName::from("events"),
+                    &TypeArenaEvent::InviteReceived { invite }
                 );
             }
+            Message::RequestPracticePassage { player, focus } => {
+                // An empty library has nothing to offer; the request is dropped.
+                let Some(library_passage) = self.state.pick_library_passage(&focus).await.expect("Failed to read passage library") else {
+                    return;
+                };
+                let passage = PracticePassage {
+                    passage_id: library_passage.id,
+                    text: library_passage.text,
+                    focus,
+                    received_at: self.runtime.system_time().micros(),
+                };
+                match self.runtime.message_origin_chain_id() {
+                    Some(origin) if origin != self.runtime.chain_id() => {
+                        self.runtime.send_message(origin, Message::PracticePassage { player, passage });
+                    }
+                    _ => {
+                        self.state.practice_passages.insert(&player, passage).expect("Failed to store practice passage");
+                    }
+                }
+            }
+            Message::PracticePassage { player, passage } => {
+                self.state.practice_passages.insert(&player, passage).expect("Failed to store practice passage");
+            }
+            Message::RaceRecorded { record } => {
+                self.state.record_history(record).await.expect("Failed to record race");
+            }
+            Message::RaceInvalidated { room_id, player } => {
+                self.state.void_race(&room_id, &player).await.expect("Failed to void race");
+            }
         }
     }
 
@@ -116,3 +414,175 @@ impl Contract for TypeArena {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl TypeArena {
+    /// The authenticated signer, if they are one of the configured admins.
+    fn admin(&mut self) -> Option<String> {
+        let signer = self.runtime.authenticated_signer()?;
+        let params = self.runtime.application_parameters();
+        params.admins.contains(&signer).then(|| signer.to_string())
+    }
+
+    fn emit_moderation(&mut self, record: ModerationRecord) {
+        self.runtime.emit(
+            StreamName::from("events"),
+            &TypeArenaEvent::Moderated { record }
+        );
+    }
+
+    fn emit_achievements(&mut self, player: &str, unlocked: Vec<Achievement>) {
+        for achievement in unlocked {
+            self.runtime.emit(
+                StreamName::from("events"),
+                &TypeArenaEvent::AchievementUnlocked { player: player.to_string(), achievement }
+            );
+        }
+    }
+
+    /// Pays a reward from the treasury, capped by what is left of the day's budget.
+    /// What the player owes for invalidated results is withheld first. Players whose
+    /// id is not an account owner are skipped, as are rewards the treasury cannot
+    /// cover, so a race never fails over a payout.
+    async fn pay_reward(&mut self, player: &str, chain_id: Option<ChainId>, reason: RewardReason, reference: String, position: u32) {
+        let params = self.runtime.application_parameters();
+        let Some(token_app) = params.token_app else {
+            return;
+        };
+        let Ok(owner) = player.parse::<AccountOwner>() else {
+            return;
+        };
+        let reward = match reason {
+            RewardReason::RacePlacement => params.rewards.placement(position),
+            RewardReason::TournamentWin => params.rewards.tournament_win,
+            RewardReason::SeasonStanding => params.rewards.season_rank(position),
+        };
+        let withheld = self.state.reward_debt(player).await.expect("Failed to read reward debt").min(reward);
+        let now = self.runtime.system_time().micros();
+        let amount = self.state.reward_budget.get().available(now, params.rewards.daily_budget, reward.saturating_sub(withheld));
+        if amount > Amount::ZERO {
+            let treasury = AccountOwner::from(self.runtime.application_id().forget_abi());
+            let target_account = Account { chain_id: chain_id.unwrap_or_else(|| self.runtime.chain_id()), owner };
+            let operation = TokenOperation::Transfer { owner: treasury, amount, target_account };
+            if let TokenResponse::Error(_) = self.runtime.call_application(true, token_app, &operation) {
+                return;
+            }
+
+            let payout = RewardPayout { player: player.to_string(), amount, reason, reference, position, paid_at: now };
+            self.state.record_reward(payout.clone()).await.expect("Failed to record reward");
+            self.runtime.emit(
+                StreamName::from("events"),
+                &TypeArenaEvent::RewardPaid { payout }
+            );
+        }
+        if withheld > Amount::ZERO {
+            self.state.settle_reward_debt(player, withheld).await.expect("Failed to settle reward debt");
+        }
+    }
+
+    /// Cancels a room's spectator market, refunding every bet.
+    fn cancel_market(&mut self, market_id: u64) {
+        let market_app = self.runtime.application_parameters().market_app.expect("No market application configured");
+        let _: MarketResponse = self.runtime.call_application(
+            true,
+            market_app,
+            &MarketOperation::CancelMarket { market_id },
+        );
+    }
+
+    /// Opens a spectator market on the room's bet. The host's signature is forwarded,
+    /// so the market's creation fee is charged to them.
+    async fn open_market(&mut self, room_id: String, bet: RaceBet) -> u64 {
+        let market_app = self.runtime.application_parameters().market_app.expect("No market application configured");
+        let operation = MarketOperation::CreateMarket {
+            market_type: MarketType::BinaryEvent { question: bet.question(&room_id) },
+            duration_minutes: betting::MARKET_DURATION_MINUTES,
+        };
+        let market_id = match self.runtime.call_application(true, market_app, &operation) {
+            MarketResponse::MarketId(market_id) => market_id,
+            response => panic!("Unexpected market response: {response:?}"),
+        };
+        self.state
+            .attach_market(room_id, RaceMarket { market_id, bet })
+            .await
+            .expect("Failed to attach market");
+        market_id
+    }
+
+    /// Joins a room on this chain directly, or asks the host chain to add the player.
+    async fn join_room(&mut self, room_id: String, host_chain_id: ChainId, player: String) {
+        if host_chain_id == self.runtime.chain_id() {
+            self.state.join_room(room_id.clone(), player.clone()).await.expect("Failed to join room locally");
+            self.note_player_chain(&room_id, &player).await;
+            self.runtime.emit(
+                StreamName::from("events"),
+                &TypeArenaEvent::PlayerJoined { room_id, player }
+            );
+        } else {
+            let message = Message::JoinRoom { room_id, player };
+            self.runtime.send_message(host_chain_id, message);
+        }
+    }
+
+    /// Remembers the chain the current operation or message came from as the
+    /// player's chain for this room.
+    async fn note_player_chain(&mut self, room_id: &str, player: &str) {
+        let chain_id = self
+            .runtime
+            .message_origin_chain_id()
+            .unwrap_or_else(|| self.runtime.chain_id());
+        self.state
+            .record_player_chain(room_id.to_string(), player.to_string(), chain_id)
+            .await
+            .expect("Failed to record player chain");
+    }
+
+    /// Delivers a finished race to the player's chain history, keeping it here if
+    /// the player races from this chain or their chain is unknown.
+    async fn confirm_race(&mut self, chain_id: Option<ChainId>, record: RaceRecord) {
+        match chain_id {
+            Some(chain_id) if chain_id != self.runtime.chain_id() => {
+                self.runtime.send_message(chain_id, Message::RaceRecorded { record });
+            }
+            _ => self.state.record_history(record).await.expect("Failed to record race"),
+        }
+    }
+
+    /// Removes an invalidated race from the history it was confirmed to.
+    async fn void_race(&mut self, chain_id: Option<ChainId>, room_id: String, player: String) {
+        match chain_id {
+            Some(chain_id) if chain_id != self.runtime.chain_id() => {
+                self.runtime.send_message(chain_id, Message::RaceInvalidated { room_id, player });
+            }
+            _ => self.state.void_race(&room_id, &player).await.expect("Failed to void race"),
+        }
+    }
+
+    async fn join_team(&mut self, room_id: String, team: u32, player: String) {
+        self.state.join_team(room_id.clone(), team, player.clone()).await.expect("Failed to join team");
+        self.note_player_chain(&room_id, &player).await;
+        self.runtime.emit(
+            StreamName::from("events"),
+            &TypeArenaEvent::TeamJoined { room_id, team, player }
+        );
+    }
+
+    async fn submit_segment(&mut self, room_id: String, player: String, submission: Submission) {
+        let now = self.runtime.system_time().micros();
+        let (team, result, unlocked) = self.state.submit_segment(room_id.clone(), player.clone(), submission, now).await.expect("Failed to submit segment");
+        self.note_player_chain(&room_id, &player).await;
+        self.runtime.emit(
+            StreamName::from("events"),
+            &TypeArenaEvent::SegmentSubmitted { room_id, team, segment: result.segment, player: player.clone(), time_ms: result.time_ms }
+        );
+        self.emit_achievements(&player, unlocked);
+    }
+
+    async fn mint_badge(&mut self, player: String, achievement: Achievement) {
+        let minted_at = self.runtime.system_time().micros();
+        let badge = self.state.mint_badge(player.clone(), achievement, minted_at).await.expect("Failed to mint badge");
+        self.runtime.emit(
+            StreamName::from("events"),
+            &TypeArenaEvent::BadgeMinted { player, token_id: badge.token_id, achievement }
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use linera_sdk::serde_json;
+use serde::{Deserialize, Serialize};
+
+use crate::relay::{self, RoomMode};
+use crate::state::{PlayerStats, Room};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 100;
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// One player's result in a finished room, flattened for export.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct RoomResultRow {
+    pub room_id: String,
+    pub host: String,
+    pub mode: RoomMode,
+    pub start_time: Option<u64>,
+    /// Relay team, for relay rooms.
+    pub team: Option<u32>,
+    pub player: String,
+    /// 1-based final place. Relay members share their team's place.
+    pub placement: u32,
+    pub wpm: u32,
+    pub time_ms: u64,
+    pub accuracy: Option<u32>,
+}
+
+/// A player's all-time stats, flattened for export.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct StatsRow {
+    pub player: String,
+    pub wins: u32,
+    pub total_races: u32,
+    pub best_wpm: u32,
+    pub win_streak: u32,
+    pub best_win_streak: u32,
+    pub tournaments_won: u32,
+}
+
+/// A page of finished-room results.
+#[derive(Clone, Debug, Default, Serialize, async_graphql::SimpleObject)]
+pub struct RoomExportPage {
+    pub rows: Vec<RoomResultRow>,
+    /// The rows as newline-delimited JSON.
+    pub ndjson: String,
+    /// Offset of the next page, or `None` on the last page.
+    pub next_offset: Option<u32>,
+}
+
+/// A page of player stats.
+#[derive(Clone, Debug, Default, Serialize, async_graphql::SimpleObject)]
+pub struct StatsExportPage {
+    pub rows: Vec<StatsRow>,
+    /// The rows as newline-delimited JSON.
+    pub ndjson: String,
+    /// Offset of the next page, or `None` on the last page.
+    pub next_offset: Option<u32>,
+}
+
+/// Result rows of a room, best place first. Unfinished rooms have none.
+pub fn room_rows(room: &Room) -> Vec<RoomResultRow> {
+    if !room.is_finished {
+        return vec![];
+    }
+    let row = |team, player: &str, placement, wpm, time_ms, accuracy| RoomResultRow {
+        room_id: room.id.clone(),
+        host: room.host.clone(),
+        mode: room.mode,
+        start_time: room.start_time,
+        team,
+        player: player.to_string(),
+        placement,
+        wpm,
+        time_ms,
+        accuracy,
+    };
+    match room.mode {
+        RoomMode::Solo => room
+            .ranked_results()
+            .into_iter()
+            .enumerate()
+            .map(|(place, result)| {
+                row(None, &result.address, place as u32 + 1, result.wpm, result.finish_time_ms, result.accuracy)
+            })
+            .collect(),
+        RoomMode::Relay => relay::standings(&room.teams, room.segments.len() as u32)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(place, standing)| {
+                room.teams[standing.team as usize]
+                    .segment_results
+                    .iter()
+                    .map(|result| {
+                        row(Some(standing.team), &result.player, place as u32 + 1, result.wpm, result.time_ms, None)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+    }
+}
+
+pub fn stats_row(player: String, stats: PlayerStats) -> StatsRow {
+    StatsRow {
+        player,
+        wins: stats.wins,
+        total_races: stats.total_races,
+        best_wpm: stats.best_wpm,
+        win_streak: stats.win_streak,
+        best_win_streak: stats.best_win_streak,
+        tournaments_won: stats.tournaments_won,
+    }
+}
+
+/// Serializes rows as newline-delimited JSON.
+pub fn to_ndjson<T: Serialize>(rows: &[T]) -> String {
+    rows.iter()
+        .map(|row| serde_json::to_string(row).expect("Export rows serialize to JSON") + "\n")
+        .collect()
+}
+
+/// Clamps a requested page size to `1..=MAX_PAGE_SIZE`.
+pub fn page_size(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE) as usize
+}
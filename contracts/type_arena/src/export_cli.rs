@@ -0,0 +1,99 @@
+//! Exports finished-room results and player stats from a type_arena service to CSV.
+//!
+//! Usage: `type_arena_export <application-url> [output-dir]`, where the URL is the
+//! node service's GraphQL endpoint for the application, e.g.
+//! `http://localhost:8080/chains/<chain-id>/applications/<application-id>`.
+//! Writes `rooms.csv` and `stats.csv` to the output directory (default `.`).
+
+use std::{
+    env,
+    error::Error,
+    path::{Path, PathBuf},
+    process,
+};
+
+use serde_json::{json, Value};
+use type_arena::export::MAX_PAGE_SIZE;
+
+/// GraphQL field and CSV column of every exported room column.
+const ROOM_COLUMNS: [(&str, &str); 10] = [
+    ("roomId", "room_id"),
+    ("host", "host"),
+    ("mode", "mode"),
+    ("startTime", "start_time"),
+    ("team", "team"),
+    ("player", "player"),
+    ("placement", "placement"),
+    ("wpm", "wpm"),
+    ("timeMs", "time_ms"),
+    ("accuracy", "accuracy"),
+];
+
+/// GraphQL field and CSV column of every exported stats column.
+const STATS_COLUMNS: [(&str, &str); 7] = [
+    ("player", "player"),
+    ("wins", "wins"),
+    ("totalRaces", "total_races"),
+    ("bestWpm", "best_wpm"),
+    ("winStreak", "win_streak"),
+    ("bestWinStreak", "best_win_streak"),
+    ("tournamentsWon", "tournaments_won"),
+];
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let Some(url) = args.next() else {
+        eprintln!("Usage: type_arena_export <application-url> [output-dir]");
+        process::exit(2);
+    };
+    let out_dir = PathBuf::from(args.next().unwrap_or_else(|| ".".to_string()));
+    if let Err(error) = run(&url, &out_dir) {
+        eprintln!("Export failed: {error}");
+        process::exit(1);
+    }
+}
+
+fn run(url: &str, out_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let rooms = export(url, "exportRooms", &ROOM_COLUMNS, &out_dir.join("rooms.csv"))?;
+    let players = export(url, "exportStats", &STATS_COLUMNS, &out_dir.join("stats.csv"))?;
+    println!("Exported {rooms} result rows and {players} players to {}", out_dir.display());
+    Ok(())
+}
+
+/// Pages through `field` until the last page, writing every row to `path`.
+/// Returns the number of rows written.
+fn export(url: &str, field: &str, columns: &[(&str, &str)], path: &Path) -> Result<usize, Box<dyn Error>> {
+    let selection = columns.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(" ");
+    let query = format!(
+        "query($offset: Int!, $limit: Int!) {{ page: {field}(offset: $offset, limit: $limit) {{ nextOffset rows {{ {selection} }} }} }}"
+    );
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(columns.iter().map(|(_, header)| *header))?;
+
+    let mut written = 0;
+    let mut offset = Some(0);
+    while let Some(current) = offset {
+        let response: Value = ureq::post(url)
+            .send_json(json!({ "query": query, "variables": { "offset": current, "limit": MAX_PAGE_SIZE } }))?
+            .into_json()?;
+        if let Some(errors) = response.get("errors") {
+            return Err(format!("GraphQL errors: {errors}").into());
+        }
+        let page = &response["data"]["page"];
+        for row in page["rows"].as_array().ok_or("Missing rows in response")? {
+            writer.write_record(columns.iter().map(|(name, _)| cell(&row[*name])))?;
+            written += 1;
+        }
+        offset = page["nextOffset"].as_u64();
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,54 @@
+use abi::ONE_DAY_MICROS;
+use linera_sdk::linera_base_types::ChainId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The chain a player raced from, so results can be confirmed back to it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct PlayerChain {
+    pub player: String,
+    pub chain_id: ChainId,
+}
+
+/// One finished race, as kept in a player's history.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct RaceRecord {
+    pub player: String,
+    pub room_id: String,
+    pub passage: String,
+    /// 1-based final place. Relay members share their team's place.
+    pub placement: u32,
+    pub wpm: u32,
+    /// Accuracy in percent, when the client reported it.
+    pub accuracy: Option<u32>,
+    pub timestamp: u64,
+}
+
+/// Aggregated results for one UTC day, for charting progress.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct DailyWpm {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub races: u32,
+    pub average_wpm: u32,
+    pub best_wpm: u32,
+}
+
+/// Groups records by day, oldest day first.
+pub fn wpm_by_day<'a>(records: impl IntoIterator<Item = &'a RaceRecord>) -> Vec<DailyWpm> {
+    let mut days: BTreeMap<u64, (u32, u64, u32)> = BTreeMap::new();
+    for record in records {
+        let (races, total, best) = days.entry(record.timestamp / ONE_DAY_MICROS).or_default();
+        *races += 1;
+        *total += u64::from(record.wpm);
+        *best = (*best).max(record.wpm);
+    }
+    days.into_iter()
+        .map(|(day, (races, total, best_wpm))| DailyWpm {
+            day,
+            races,
+            average_wpm: (total / u64::from(races)) as u32,
+            best_wpm,
+        })
+        .collect()
+}
@@ -1,10 +1,40 @@
+pub mod achievements;
+pub mod betting;
+pub mod config;
+pub mod export;
+pub mod history;
+pub mod moderation;
+pub mod passage;
+pub mod practice;
+pub mod relay;
+pub mod rewards;
+pub mod seasons;
+pub mod social;
 pub mod state;
+pub mod submission;
+pub mod versioning;
 
-use linera_sdk::linera_base_types::{ContractAbi, ServiceAbi, ChainId};
+use linera_sdk::linera_base_types::{AccountOwner, ApplicationId, ContractAbi, ServiceAbi, ChainId};
+use market::MarketAbi;
+use oracle::OracleAbi;
+use token::TokenAbi;
 use async_graphql::Request;
 use serde::{Deserialize, Serialize};
 
-pub use state::{TypeArenaState, Room, Tournament, PlayerStats};
+pub use achievements::{Achievement, Badge};
+pub use betting::{RaceBet, RaceBetKind, RaceMarket};
+pub use config::GameConfig;
+pub use history::{DailyWpm, RaceRecord};
+pub use moderation::{ModerationKind, ModerationRecord};
+pub use passage::{PassageInfo, ScoringRule};
+pub use practice::{KeyStat, LibraryPassage, PracticePassage, PracticeProfile};
+pub use relay::{RoomMode, Team, TeamStanding};
+pub use rewards::{RewardBudget, RewardPayout, RewardReason, RewardSchedule};
+pub use seasons::{SeasonArchive, SeasonStanding, SeasonStats};
+pub use social::{Friend, Invite};
+pub use state::{TypeArenaState, Room, RoomPhase, Tournament, PlayerStats};
+pub use submission::{KeystrokeProof, Submission};
+pub use versioning::MigrationReport;
 
 pub struct TypeArenaAbi;
 
@@ -18,24 +48,93 @@ impl ServiceAbi for TypeArenaAbi {
     type QueryResponse = async_graphql::Response;
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TypeArenaParameters {
+    /// Owners allowed to run moderation operations.
+    pub admins: Vec<AccountOwner>,
+    /// Market application spectator bets are opened on, if betting is enabled.
+    #[serde(default)]
+    pub market_app: Option<ApplicationId<MarketAbi>>,
+    /// Oracle application that resolves spectator markets. It must list this
+    /// application as a trusted outcome source.
+    #[serde(default)]
+    pub oracle_app: Option<ApplicationId<OracleAbi>>,
+    /// Token application rewards are paid in, if rewards are enabled. They are paid
+    /// from this application's own account there, which must be funded.
+    #[serde(default)]
+    pub token_app: Option<ApplicationId<TokenAbi>>,
+    #[serde(default)]
+    pub rewards: RewardSchedule,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Operation {
-    CreateRoom { room_id: String, text: String },
+    CreateRoom { room_id: String, text: String, passage: Option<PassageInfo> },
+    CreateRelayRoom { room_id: String, text: String, passage: Option<PassageInfo>, team_count: u32, team_size: u32 },
+    JoinTeam { room_id: String, team: u32, host_chain_id: ChainId },
+    SubmitSegment { room_id: String, submission: Submission, host_chain_id: ChainId },
     JoinRoom { room_id: String, host_chain_id: ChainId },
-    SubmitResult { room_id: String, wpm: u32, time_ms: u64, host_chain_id: ChainId },
+    SubmitResult { room_id: String, submission: Submission, accuracy: Option<u32>, host_chain_id: ChainId },
     FinishRoom { room_id: String },
+    /// Moves a room into its countdown, optionally opening a spectator market. Host only.
+    StartCountdown { room_id: String, bet: Option<RaceBet> },
+    /// Starts the race and locks betting. Host only.
+    StartRace { room_id: String },
+    MintBadge { achievement: Achievement, host_chain_id: ChainId },
+    CloseSeason,
+    BanPlayer { player: String, reason: String },
+    UnbanPlayer { player: String, reason: String },
+    InvalidateResult { room_id: String, player: String, reason: String },
+    DeleteRoom { room_id: String, reason: String },
+    UpdateConfig { config: GameConfig },
+    /// Rewrites up to `batch_size` records from older layouts. Admin only; repeat
+    /// until the report says it is complete.
+    MigrateState { batch_size: u32 },
+    /// Records the winner of a tournament and pays the tournament reward. Admin only.
+    AwardTournament { tournament_id: String, winner: String },
+    AddFriend { player: String, chain_id: ChainId },
+    RemoveFriend { player: String },
+    InviteToRoom { friend: String, room_id: String, host_chain_id: ChainId },
+    AcceptInvite { invite_id: u64 },
+    DeclineInvite { invite_id: u64 },
+    /// Records one non-competitive practice session on the player's own chain.
+    RecordPractice { keys: Vec<KeyStat>, bigrams: Vec<KeyStat> },
+    /// Adds a passage to this chain's practice library. Admin only.
+    AddLibraryPassage { text: String },
+    /// Asks the lobby chain for a passage that drills the player's weakest bigrams.
+    RequestPracticePassage { lobby_chain_id: ChainId },
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub enum TypeArenaEvent {
     RoomCreated { room_id: String },
     PlayerJoined { room_id: String, player: String },
     ResultSubmitted { room_id: String, player: String, wpm: u32 },
-    RoomFinished { room_id: String },
+    TeamJoined { room_id: String, team: u32, player: String },
+    SegmentSubmitted { room_id: String, team: u32, segment: u32, player: String, time_ms: u64 },
+    RoomFinished { room_id: String, team_standings: Vec<TeamStanding> },
+    CountdownStarted { room_id: String, market_id: Option<u64> },
+    RaceStarted { room_id: String },
+    AchievementUnlocked { player: String, achievement: Achievement },
+    BadgeMinted { player: String, token_id: u64, achievement: Achievement },
+    SeasonClosed { season: u32, standings: Vec<SeasonStanding> },
+    Moderated { record: ModerationRecord },
+    InviteReceived { invite: Invite },
+    RewardPaid { payout: RewardPayout },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
     JoinRoom { room_id: String, player: String },
-    SubmitResult { room_id: String, player: String, wpm: u32, time_ms: u64 },
+    SubmitResult { room_id: String, player: String, submission: Submission, accuracy: Option<u32> },
+    JoinTeam { room_id: String, team: u32, player: String },
+    SubmitSegment { room_id: String, player: String, submission: Submission },
+    MintBadge { player: String, achievement: Achievement },
+    InviteToRoom { room_id: String, host_chain_id: ChainId, from: String, to: String },
+    RequestPracticePassage { player: String, focus: Vec<String> },
+    PracticePassage { player: String, passage: PracticePassage },
+    /// Confirms a finished race to the chain the player raced from.
+    RaceRecorded { record: RaceRecord },
+    /// Withdraws a confirmed race whose result an admin invalidated.
+    RaceInvalidated { room_id: String, player: String },
 }
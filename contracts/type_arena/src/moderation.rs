@@ -0,0 +1,106 @@
+use linera_sdk::linera_base_types::Amount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Kinds of moderation action an admin can take.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum ModerationKind {
+    BanPlayer,
+    UnbanPlayer,
+    InvalidateResult,
+    DeleteRoom,
+    CloseSeason,
+    UpdateConfig,
+    MigrateState,
+    AwardTournament,
+}
+
+/// An entry in the moderation audit log.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct ModerationRecord {
+    pub id: u64,
+    pub admin: String,
+    pub kind: ModerationKind,
+    /// The affected player, if any.
+    pub player: Option<String>,
+    /// The affected room, if any.
+    pub room_id: Option<String>,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+/// What one result added to its player's stats, kept so an admin can revert it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct ResultEffect {
+    pub player: String,
+    pub wpm: u32,
+    /// Season the result counted towards.
+    pub season: u32,
+    /// Set once the room finishes.
+    pub outcome: Option<RaceOutcome>,
+    /// Placement reward paid for the result.
+    pub reward: Amount,
+}
+
+/// What finishing a room added to one racer's stats.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct RaceOutcome {
+    /// `ResultTally::ranked` after this race.
+    pub race: u32,
+    pub won: bool,
+    pub rating_delta: i64,
+    /// `best_win_streak` before this race.
+    pub best_win_streak_before: u32,
+}
+
+/// Per-player tallies that let a result be reverted without replaying every race.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct ResultTally {
+    /// Rooms the player was ranked in, counting invalidated ones.
+    pub ranked: u32,
+    /// `ranked` as of the player's last loss. Later races make up the current win streak.
+    pub last_loss: u32,
+    /// Number of results recorded at each WPM.
+    pub wpms: BTreeMap<u32, u32>,
+    /// The season `season_wpms` counts.
+    pub season: u32,
+    pub season_wpms: BTreeMap<u32, u32>,
+}
+
+impl ResultTally {
+    pub fn add(&mut self, wpm: u32, season: u32) {
+        if self.season != season {
+            self.season = season;
+            self.season_wpms.clear();
+        }
+        *self.wpms.entry(wpm).or_default() += 1;
+        *self.season_wpms.entry(wpm).or_default() += 1;
+    }
+
+    pub fn remove(&mut self, wpm: u32, season: u32) {
+        remove_one(&mut self.wpms, wpm);
+        if self.season == season {
+            remove_one(&mut self.season_wpms, wpm);
+        }
+    }
+
+    pub fn best_wpm(&self) -> u32 {
+        self.wpms.keys().next_back().copied().unwrap_or_default()
+    }
+
+    pub fn season_best_wpm(&self, season: u32) -> u32 {
+        if self.season != season {
+            return 0;
+        }
+        self.season_wpms.keys().next_back().copied().unwrap_or_default()
+    }
+}
+
+fn remove_one(counts: &mut BTreeMap<u32, u32>, wpm: u32) {
+    if let Some(count) = counts.get_mut(&wpm) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&wpm);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Language assumed for rooms created without passage metadata.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Characters that make up one word under the 5-character rules.
+const CHARS_PER_WORD: u64 = 5;
+
+/// How typed units are turned into words when scoring a passage.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum ScoringRule {
+    /// Every 5 characters, spaces included, count as one word.
+    #[default]
+    FiveCharWords,
+    /// Every grapheme cluster other than whitespace counts as one word, for CJK
+    /// and other scripts without spaces between words.
+    Graphemes,
+    /// Every 5 characters other than whitespace count as one word, so symbols
+    /// count in full and indentation does not.
+    Code,
+}
+
+/// Language and scoring rule of a room's passage.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject, async_graphql::InputObject)]
+#[graphql(input_name = "PassageInfoInput")]
+pub struct PassageInfo {
+    /// Language tag such as `en`, `ja` or `rust`.
+    pub language: String,
+    pub scoring: ScoringRule,
+}
+
+impl ScoringRule {
+    /// Number of words `text` is worth, in hundredths so short passages keep precision.
+    pub fn centiwords(self, text: &str) -> u64 {
+        match self {
+            ScoringRule::FiveCharWords => {
+                text.graphemes(true).count() as u64 * 100 / CHARS_PER_WORD
+            }
+            ScoringRule::Graphemes => {
+                text.graphemes(true).filter(|g| !g.trim().is_empty()).count() as u64 * 100
+            }
+            ScoringRule::Code => {
+                text.chars().filter(|c| !c.is_whitespace()).count() as u64 * 100 / CHARS_PER_WORD
+            }
+        }
+    }
+
+    /// Words per minute for typing `text` in `time_ms`. A zero time scores
+    /// `u32::MAX` so it fails any plausibility check.
+    pub fn wpm(self, text: &str, time_ms: u64) -> u32 {
+        if time_ms == 0 {
+            return u32::MAX;
+        }
+        let wpm = self.centiwords(text) * 60_000 / 100 / time_ms;
+        u32::try_from(wpm).unwrap_or(u32::MAX)
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Attempts a key or bigram needs before it is ranked, so one slip does not dominate.
+pub const MIN_ATTEMPTS: u32 = 5;
+
+/// Weak bigrams sent along with a practice passage request.
+pub const FOCUS_BIGRAMS: usize = 5;
+
+/// Error and latency totals for one key or bigram.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject, async_graphql::InputObject)]
+#[graphql(input_name = "KeyStatInput")]
+pub struct KeyStat {
+    pub key: String,
+    pub attempts: u32,
+    pub errors: u32,
+    pub total_latency_ms: u64,
+}
+
+impl KeyStat {
+    pub fn error_permille(&self) -> u32 {
+        if self.attempts == 0 {
+            return 0;
+        }
+        (u64::from(self.errors.min(self.attempts)) * 1000 / u64::from(self.attempts)) as u32
+    }
+
+    pub fn average_latency_ms(&self) -> u64 {
+        if self.attempts == 0 {
+            return 0;
+        }
+        self.total_latency_ms / u64::from(self.attempts)
+    }
+}
+
+/// Accumulated practice statistics of one player.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct PracticeProfile {
+    pub sessions: u32,
+    pub keys: Vec<KeyStat>,
+    pub bigrams: Vec<KeyStat>,
+}
+
+impl PracticeProfile {
+    /// Adds the totals of one practice session.
+    pub fn record(&mut self, keys: Vec<KeyStat>, bigrams: Vec<KeyStat>) {
+        self.sessions += 1;
+        merge(&mut self.keys, keys);
+        merge(&mut self.bigrams, bigrams);
+    }
+}
+
+/// A passage in the lobby chain's practice library.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct LibraryPassage {
+    pub id: u64,
+    pub text: String,
+}
+
+/// The latest practice passage the lobby chain picked for a player.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct PracticePassage {
+    pub passage_id: u64,
+    pub text: String,
+    /// The weak bigrams the passage was chosen for.
+    pub focus: Vec<String>,
+    pub received_at: u64,
+}
+
+fn merge(stats: &mut Vec<KeyStat>, samples: Vec<KeyStat>) {
+    for sample in samples {
+        match stats.iter_mut().find(|stat| stat.key == sample.key) {
+            Some(stat) => {
+                stat.attempts = stat.attempts.saturating_add(sample.attempts);
+                stat.errors = stat.errors.saturating_add(sample.errors);
+                stat.total_latency_ms = stat.total_latency_ms.saturating_add(sample.total_latency_ms);
+            }
+            None => stats.push(sample),
+        }
+    }
+}
+
+/// The `limit` weakest entries with enough attempts: highest error rate first,
+/// then slowest.
+pub fn weakest(stats: &[KeyStat], limit: usize) -> Vec<KeyStat> {
+    let mut ranked: Vec<&KeyStat> = stats.iter().filter(|stat| stat.attempts >= MIN_ATTEMPTS).collect();
+    ranked.sort_by_key(|stat| (Reverse(stat.error_permille()), Reverse(stat.average_latency_ms()), stat.key.clone()));
+    ranked.into_iter().take(limit).cloned().collect()
+}
+
+/// Scores a passage by how often it contains the focus bigrams, weaker bigrams
+/// (earlier in `focus`) counting more.
+pub fn focus_score(text: &str, focus: &[String]) -> u64 {
+    let text = text.to_lowercase();
+    focus
+        .iter()
+        .enumerate()
+        .filter(|(_, bigram)| !bigram.is_empty())
+        .map(|(rank, bigram)| {
+            let occurrences = text.matches(bigram.to_lowercase().as_str()).count() as u64;
+            occurrences * (focus.len() - rank) as u64
+        })
+        .sum()
+}
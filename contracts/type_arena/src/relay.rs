@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+/// How players compete in a room.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum RoomMode {
+    /// Every player types the whole passage on their own.
+    #[default]
+    Solo,
+    /// Teams split the passage and type it one segment per member.
+    Relay,
+}
+
+/// A relay team. Members type the segments in the order they joined.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct Team {
+    pub index: u32,
+    pub members: Vec<String>,
+    pub segment_results: Vec<SegmentResult>,
+}
+
+/// Team layout requested when a relay room is created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RelaySetup {
+    pub team_count: u32,
+    pub team_size: u32,
+}
+
+/// One member's leg of a relay.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct SegmentResult {
+    pub segment: u32,
+    pub player: String,
+    pub wpm: u32,
+    pub time_ms: u64,
+}
+
+/// Final position of a team, as reported when the room finishes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct TeamStanding {
+    pub team: u32,
+    pub members: Vec<String>,
+    pub segments_completed: u32,
+    pub total_time_ms: u64,
+    pub finished: bool,
+}
+
+impl Team {
+    /// The segment this team is currently allowed to submit.
+    pub fn next_segment(&self) -> u32 {
+        self.segment_results.len() as u32
+    }
+
+    pub fn total_time_ms(&self) -> u64 {
+        self.segment_results.iter().map(|result| result.time_ms).sum()
+    }
+
+    pub fn standing(&self, segment_count: u32) -> TeamStanding {
+        TeamStanding {
+            team: self.index,
+            members: self.members.clone(),
+            segments_completed: self.next_segment(),
+            total_time_ms: self.total_time_ms(),
+            finished: self.next_segment() >= segment_count,
+        }
+    }
+}
+
+/// Ranks teams: finished teams by total time, then unfinished teams by progress.
+pub fn standings(teams: &[Team], segment_count: u32) -> Vec<TeamStanding> {
+    let mut standings: Vec<TeamStanding> =
+        teams.iter().map(|team| team.standing(segment_count)).collect();
+    standings.sort_by_key(|standing| {
+        (
+            !standing.finished,
+            std::cmp::Reverse(standing.segments_completed),
+            standing.total_time_ms,
+        )
+    });
+    standings
+}
+
+/// Splits a passage into `count` consecutive segments of roughly equal word count.
+/// Returns `None` when the passage has fewer words than segments.
+pub fn split_passage(text: &str, count: u32) -> Option<Vec<String>> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let count = count as usize;
+    if count == 0 || words.len() < count {
+        return None;
+    }
+    let base = words.len() / count;
+    let extra = words.len() % count;
+    let mut segments = Vec::with_capacity(count);
+    let mut start = 0;
+    for i in 0..count {
+        let len = base + usize::from(i < extra);
+        segments.push(words[start..start + len].join(" "));
+        start += len;
+    }
+    Some(segments)
+}
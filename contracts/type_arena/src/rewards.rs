@@ -0,0 +1,85 @@
+use abi::ONE_DAY_MICROS;
+use linera_sdk::linera_base_types::Amount;
+use serde::{Deserialize, Serialize};
+
+/// Fewest racers a room needs to finish with for its placements to earn rewards,
+/// so nobody can farm the treasury by racing alone.
+pub const MIN_REWARDED_FINISHERS: usize = 2;
+
+/// Token rewards paid from the application's treasury account. The default pays nothing.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct RewardSchedule {
+    /// Reward per race placement, first place first. Places past the end earn nothing.
+    /// Only rooms that started racing and finished with `MIN_REWARDED_FINISHERS` racers pay them.
+    pub placements: Vec<Amount>,
+    pub tournament_win: Amount,
+    /// Reward per closing season rank, first rank first.
+    pub season_standings: Vec<Amount>,
+    /// Most the treasury pays out per UTC day, across all rewards.
+    pub daily_budget: Amount,
+}
+
+impl RewardSchedule {
+    /// Reward for a 1-based race placement.
+    pub fn placement(&self, placement: u32) -> Amount {
+        tiered(&self.placements, placement)
+    }
+
+    /// Reward for a 1-based season rank.
+    pub fn season_rank(&self, rank: u32) -> Amount {
+        tiered(&self.season_standings, rank)
+    }
+}
+
+fn tiered(tiers: &[Amount], position: u32) -> Amount {
+    position
+        .checked_sub(1)
+        .and_then(|index| tiers.get(index as usize))
+        .copied()
+        .unwrap_or(Amount::ZERO)
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum RewardReason {
+    RacePlacement,
+    TournamentWin,
+    SeasonStanding,
+}
+
+/// A reward paid from the treasury.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct RewardPayout {
+    pub player: String,
+    pub amount: Amount,
+    pub reason: RewardReason,
+    /// The room id, tournament id or season number the reward is for.
+    pub reference: String,
+    /// 1-based placement or rank. Always 1 for tournament wins.
+    pub position: u32,
+    pub paid_at: u64,
+}
+
+/// What the treasury has paid out on the current day.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct RewardBudget {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub paid: Amount,
+}
+
+impl RewardBudget {
+    /// How much of a reward fits in the budget at `now`.
+    pub fn available(&self, now: u64, daily_budget: Amount, amount: Amount) -> Amount {
+        let paid = if self.day == now / ONE_DAY_MICROS { self.paid } else { Amount::ZERO };
+        amount.min(daily_budget.saturating_sub(paid))
+    }
+
+    /// Records a payout at `now`, starting a new day's tally when the day has changed.
+    pub fn spend(&mut self, now: u64, amount: Amount) {
+        let day = now / ONE_DAY_MICROS;
+        if self.day != day {
+            *self = RewardBudget { day, paid: Amount::ZERO };
+        }
+        self.paid = self.paid.saturating_add(amount);
+    }
+}
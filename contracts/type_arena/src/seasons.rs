@@ -0,0 +1,90 @@
+use abi::ONE_DAY_MICROS;
+use serde::{Deserialize, Serialize};
+
+/// How long a season runs before anyone may close it (30 days).
+pub const SEASON_LENGTH_MICROS: u64 = 30 * ONE_DAY_MICROS;
+
+/// Number of players kept in a season's archived standings.
+pub const SEASON_ARCHIVE_SIZE: usize = 10;
+
+/// Rating every player starts a season with.
+pub const INITIAL_RATING: u32 = 1000;
+
+/// Rating moved per place when a room finishes.
+pub const RATING_STEP: i64 = 8;
+
+/// A player's record for the current season.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct SeasonStats {
+    pub wins: u32,
+    pub total_races: u32,
+    pub best_wpm: u32,
+    pub rating: u32,
+}
+
+impl Default for SeasonStats {
+    fn default() -> Self {
+        SeasonStats {
+            wins: 0,
+            total_races: 0,
+            best_wpm: 0,
+            rating: INITIAL_RATING,
+        }
+    }
+}
+
+impl SeasonStats {
+    pub fn apply_rating_delta(&mut self, delta: i64) {
+        self.rating = (i64::from(self.rating) + delta).clamp(0, i64::from(u32::MAX)) as u32;
+    }
+}
+
+/// One row of an archived season leaderboard.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct SeasonStanding {
+    pub rank: u32,
+    pub player: String,
+    pub rating: u32,
+    pub wins: u32,
+    pub total_races: u32,
+    pub best_wpm: u32,
+}
+
+/// Final top standings of a closed season.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct SeasonArchive {
+    pub season: u32,
+    pub started_at: u64,
+    pub closed_at: u64,
+    pub standings: Vec<SeasonStanding>,
+}
+
+/// Zero-sum rating change for finishing at `place` (0-based) out of `field` ranked groups.
+pub fn rating_delta(place: usize, field: usize) -> i64 {
+    if field < 2 {
+        return 0;
+    }
+    RATING_STEP * (field as i64 - 1 - 2 * place as i64)
+}
+
+/// Orders players by rating, then wins, then best WPM, and keeps the top `limit`.
+pub fn top_standings(mut entries: Vec<(String, SeasonStats)>, limit: usize) -> Vec<SeasonStanding> {
+    entries.sort_by(|(a_player, a), (b_player, b)| {
+        (b.rating, b.wins, b.best_wpm)
+            .cmp(&(a.rating, a.wins, a.best_wpm))
+            .then_with(|| a_player.cmp(b_player))
+    });
+    entries
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(index, (player, stats))| SeasonStanding {
+            rank: index as u32 + 1,
+            player,
+            rating: stats.rating,
+            wins: stats.wins,
+            total_races: stats.total_races,
+            best_wpm: stats.best_wpm,
+        })
+        .collect()
+}
@@ -1,16 +1,17 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
-use self::state::{TypeArenaState, Room, PlayerStats};
 use async_graphql::{Schema, Object, EmptySubscription};
 use linera_sdk::{
     Service, ServiceRuntime, 
     views::View, 
     linera_base_types::{WithServiceAbi, ChainId}
 };
-use std::sync::Arc;
-use type_arena::{TypeArenaAbi, Operation};
+use std::{collections::BTreeMap, sync::Arc};
+use type_arena::{
+    Achievement, Badge, DailyWpm, Friend, Invite, KeyStat, PassageInfo, PracticePassage, PracticeProfile, RaceBet, GameConfig, ModerationRecord, RaceRecord, TypeArenaAbi, TypeArenaParameters, TypeArenaState,
+    Operation, Room, PlayerStats, Submission, RewardBudget, RewardPayout, SeasonArchive, SeasonStats, practice,
+    export::{self, RoomExportPage, StatsExportPage},
+};
 
 linera_sdk::service!(TypeArena);
 
@@ -30,15 +31,234 @@ struct QueryRoot {
 #[Object]
 impl QueryRoot {
     async fn rooms(&self, key: String) -> Option<Room> {
-        self.state.rooms.get(&key).await.ok().flatten()
+        self.state.room(&key).await.ok().flatten()
     }
 
     async fn room(&self, room_id: String) -> Option<Room> {
-        self.state.rooms.get(&room_id).await.ok().flatten()
+        self.state.room(&room_id).await.ok().flatten()
     }
 
     async fn player_stats(&self, key: String) -> Option<PlayerStats> {
-        self.state.player_stats.get(&key).await.ok().flatten()
+        self.state.stats(&key).await.ok().flatten()
+    }
+
+    /// Results of finished rooms, one row per player, paged over rooms in the order
+    /// they finished. Deleted rooms count towards the offset but add no rows.
+    async fn export_rooms(&self, offset: Option<u32>, limit: Option<u32>) -> RoomExportPage {
+        let offset = offset.unwrap_or(0) as usize;
+        let Ok((rooms, next_offset)) = self.state.finished_room_page(offset, export::page_size(limit)).await else {
+            return RoomExportPage::default();
+        };
+        let rows: Vec<_> = rooms.iter().flat_map(export::room_rows).collect();
+        RoomExportPage {
+            ndjson: export::to_ndjson(&rows),
+            rows,
+            next_offset: next_offset.map(|offset| offset as u32),
+        }
+    }
+
+    /// All-time player stats, paged over players in storage order.
+    async fn export_stats(&self, offset: Option<u32>, limit: Option<u32>) -> StatsExportPage {
+        let offset = offset.unwrap_or(0) as usize;
+        let Ok((players, next_offset)) = self.state.players_with_stats_page(offset, export::page_size(limit)).await else {
+            return StatsExportPage::default();
+        };
+        let mut rows = Vec::new();
+        for player in players {
+            if let Ok(Some(stats)) = self.state.stats(&player).await {
+                rows.push(export::stats_row(player, stats));
+            }
+        }
+        StatsExportPage {
+            ndjson: export::to_ndjson(&rows),
+            rows,
+            next_offset: next_offset.map(|offset| offset as u32),
+        }
+    }
+
+    /// The layout version all records were last migrated to.
+    async fn schema_version(&self) -> u32 {
+        *self.state.schema_version.get()
+    }
+
+    async fn config(&self) -> GameConfig {
+        self.state.config.get().clone()
+    }
+
+    async fn current_season(&self) -> u32 {
+        *self.state.season.get()
+    }
+
+    async fn season_started_at(&self) -> u64 {
+        *self.state.season_started_at.get()
+    }
+
+    async fn season_stats(&self, player: String) -> Option<SeasonStats> {
+        self.state.season_stats.get(&player).await.ok().flatten()
+    }
+
+    async fn season_archive(&self, season: u32) -> Option<SeasonArchive> {
+        self.state.season_archive.get(&season).await.ok().flatten()
+    }
+
+    async fn is_banned(&self, player: String) -> bool {
+        self.state.banned.contains_key(&player).await.unwrap_or(false)
+    }
+
+    /// Moderation audit log entries in `[start, end)`, oldest first.
+    async fn audit_log(&self, start: Option<u64>, end: Option<u64>) -> Vec<ModerationRecord> {
+        let count = self.state.audit_log.count();
+        let end = end.map_or(count, |end| (end as usize).min(count));
+        let start = start.map_or(0, |start| (start as usize).min(end));
+        self.state.audit_log.read(start..end).await.unwrap_or_default()
+    }
+
+    /// Races recorded on this chain, newest first. Filters by player when given.
+    /// Invalidated races are left out, so a page without a player filter may hold
+    /// fewer than `limit` records.
+    async fn my_races(&self, player: Option<String>, offset: Option<u32>, limit: Option<u32>) -> Vec<RaceRecord> {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(20) as usize;
+        let Some(player) = player else {
+            let end = self.state.race_history.count().saturating_sub(offset);
+            let start = end.saturating_sub(limit);
+            let records = self.state.race_history.read(start..end).await.unwrap_or_default();
+            let mut records = self.without_voided(start, records).await;
+            records.reverse();
+            return records;
+        };
+        let positions = self.state.race_index.get(&player).await.ok().flatten().unwrap_or_default();
+        self.records_at(positions.into_iter().rev().skip(offset).take(limit)).await
+    }
+
+    /// Per-day WPM aggregates of the races recorded on this chain.
+    async fn wpm_by_day(&self, player: Option<String>) -> Vec<DailyWpm> {
+        type_arena::history::wpm_by_day(&self.race_history(player).await)
+    }
+
+    async fn friends(&self, player: String) -> Vec<Friend> {
+        self.state.friends.get(&player).await.ok().flatten().unwrap_or_default()
+    }
+
+    /// Invitations waiting for the player to accept or decline.
+    async fn pending_invites(&self, player: String) -> Vec<Invite> {
+        let mut invites = Vec::new();
+        self.state
+            .invites
+            .for_each_index_value(|_, invite| {
+                if invite.to == player {
+                    invites.push(invite.into_owned());
+                }
+                Ok(())
+            })
+            .await
+            .ok();
+        invites
+    }
+
+    async fn practice_profile(&self, player: String) -> Option<PracticeProfile> {
+        self.state.practice.get(&player).await.ok().flatten()
+    }
+
+    /// Keys with the highest error rate, then the slowest. Defaults to 5 entries.
+    async fn weakest_keys(&self, player: String, limit: Option<u32>) -> Vec<KeyStat> {
+        let profile = self.state.practice.get(&player).await.ok().flatten().unwrap_or_default();
+        practice::weakest(&profile.keys, limit.unwrap_or(5) as usize)
+    }
+
+    /// Bigrams with the highest error rate, then the slowest. Defaults to 5 entries.
+    async fn weakest_bigrams(&self, player: String, limit: Option<u32>) -> Vec<KeyStat> {
+        let profile = self.state.practice.get(&player).await.ok().flatten().unwrap_or_default();
+        practice::weakest(&profile.bigrams, limit.unwrap_or(5) as usize)
+    }
+
+    async fn practice_passage(&self, player: String) -> Option<PracticePassage> {
+        self.state.practice_passages.get(&player).await.ok().flatten()
+    }
+
+    /// Rewards paid from the treasury, newest first, optionally for one player.
+    async fn reward_ledger(&self, player: Option<String>, offset: Option<u32>, limit: Option<u32>) -> Vec<RewardPayout> {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(20) as usize;
+        let Some(player) = player else {
+            let end = self.state.reward_ledger.count().saturating_sub(offset);
+            let start = end.saturating_sub(limit);
+            let mut payouts = self.state.reward_ledger.read(start..end).await.unwrap_or_default();
+            payouts.reverse();
+            return payouts;
+        };
+        let positions = self.state.reward_index.get(&player).await.ok().flatten().unwrap_or_default();
+        let mut payouts = Vec::new();
+        for position in positions.into_iter().rev().skip(offset).take(limit) {
+            if let Ok(Some(payout)) = self.state.reward_ledger.get(position as usize).await {
+                payouts.push(payout);
+            }
+        }
+        payouts
+    }
+
+    /// What the treasury has paid out on its current day.
+    async fn reward_budget(&self) -> RewardBudget {
+        self.state.reward_budget.get().clone()
+    }
+
+    async fn achievements(&self, player: String) -> Vec<Achievement> {
+        self.state.achievements.get(&player).await.ok().flatten().unwrap_or_default()
+    }
+
+    async fn badge(&self, token_id: u64) -> Option<Badge> {
+        self.state.badges.get(&token_id).await.ok().flatten()
+    }
+
+    async fn badges(&self, player: String) -> Vec<Badge> {
+        let token_ids = self.state.player_badges.get(&player).await.ok().flatten().unwrap_or_default();
+        let mut badges = Vec::new();
+        for token_id in token_ids {
+            if let Some(badge) = self.state.badges.get(&token_id).await.ok().flatten() {
+                badges.push(badge);
+            }
+        }
+        badges
+    }
+}
+
+impl QueryRoot {
+    async fn race_history(&self, player: Option<String>) -> Vec<RaceRecord> {
+        let Some(player) = player else {
+            let count = self.state.race_history.count();
+            let records = self.state.race_history.read(0..count).await.unwrap_or_default();
+            return self.without_voided(0, records).await;
+        };
+        let positions = self.state.race_index.get(&player).await.ok().flatten().unwrap_or_default();
+        self.records_at(positions).await
+    }
+
+    /// Drops records, read from position `start` on, that were voided from their
+    /// player's index.
+    async fn without_voided(&self, start: usize, records: Vec<RaceRecord>) -> Vec<RaceRecord> {
+        let mut indexes: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        let mut listed = Vec::new();
+        for (position, record) in (start as u32..).zip(records) {
+            if !indexes.contains_key(&record.player) {
+                let positions = self.state.race_index.get(&record.player).await.ok().flatten().unwrap_or_default();
+                indexes.insert(record.player.clone(), positions);
+            }
+            if indexes[&record.player].binary_search(&position).is_ok() {
+                listed.push(record);
+            }
+        }
+        listed
+    }
+
+    /// The history records at the given positions, in that order.
+    async fn records_at(&self, positions: impl IntoIterator<Item = u32>) -> Vec<RaceRecord> {
+        let mut records = Vec::new();
+        for position in positions {
+            if let Ok(Some(record)) = self.state.race_history.get(position as usize).await {
+                records.push(record);
+            }
+        }
+        records
     }
 }
 
@@ -46,25 +266,120 @@ struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
-    async fn create_room(&self, room_id: String, text: String) -> Vec<u8> {
-        bcs::to_bytes(&Operation::CreateRoom { room_id, text }).unwrap()
+    async fn create_room(&self, room_id: String, text: String, passage: Option<PassageInfo>) -> Vec<u8> {
+        bcs::to_bytes(&Operation::CreateRoom { room_id, text, passage }).unwrap()
+    }
+
+    async fn create_relay_room(
+        &self,
+        room_id: String,
+        text: String,
+        passage: Option<PassageInfo>,
+        team_count: u32,
+        team_size: u32,
+    ) -> Vec<u8> {
+        bcs::to_bytes(&Operation::CreateRelayRoom { room_id, text, passage, team_count, team_size }).unwrap()
+    }
+
+    async fn start_countdown(&self, room_id: String, bet: Option<RaceBet>) -> Vec<u8> {
+        bcs::to_bytes(&Operation::StartCountdown { room_id, bet }).unwrap()
+    }
+
+    async fn start_race(&self, room_id: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::StartRace { room_id }).unwrap()
+    }
+
+    async fn join_team(&self, room_id: String, team: u32, host_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::JoinTeam { room_id, team, host_chain_id }).unwrap()
+    }
+
+    async fn submit_segment(&self, room_id: String, submission: Submission, host_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::SubmitSegment { room_id, submission, host_chain_id }).unwrap()
     }
 
     async fn join_room(&self, room_id: String, host_chain_id: ChainId) -> Vec<u8> {
         bcs::to_bytes(&Operation::JoinRoom { room_id, host_chain_id }).unwrap()
     }
 
-    async fn submit_result(&self, room_id: String, wpm: u32, time_ms: u64, host_chain_id: ChainId) -> Vec<u8> {
-        bcs::to_bytes(&Operation::SubmitResult { room_id, wpm, time_ms, host_chain_id }).unwrap()
+    async fn submit_result(&self, room_id: String, submission: Submission, accuracy: Option<u32>, host_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::SubmitResult { room_id, submission, accuracy, host_chain_id }).unwrap()
     }
 
     async fn finish_room(&self, room_id: String) -> Vec<u8> {
         bcs::to_bytes(&Operation::FinishRoom { room_id }).unwrap()
     }
+
+    async fn close_season(&self) -> Vec<u8> {
+        bcs::to_bytes(&Operation::CloseSeason).unwrap()
+    }
+
+    async fn ban_player(&self, player: String, reason: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::BanPlayer { player, reason }).unwrap()
+    }
+
+    async fn unban_player(&self, player: String, reason: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::UnbanPlayer { player, reason }).unwrap()
+    }
+
+    async fn invalidate_result(&self, room_id: String, player: String, reason: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::InvalidateResult { room_id, player, reason }).unwrap()
+    }
+
+    async fn update_config(&self, config: GameConfig) -> Vec<u8> {
+        bcs::to_bytes(&Operation::UpdateConfig { config }).unwrap()
+    }
+
+    async fn delete_room(&self, room_id: String, reason: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::DeleteRoom { room_id, reason }).unwrap()
+    }
+
+    async fn add_friend(&self, player: String, chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::AddFriend { player, chain_id }).unwrap()
+    }
+
+    async fn remove_friend(&self, player: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::RemoveFriend { player }).unwrap()
+    }
+
+    async fn invite_to_room(&self, friend: String, room_id: String, host_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::InviteToRoom { friend, room_id, host_chain_id }).unwrap()
+    }
+
+    async fn accept_invite(&self, invite_id: u64) -> Vec<u8> {
+        bcs::to_bytes(&Operation::AcceptInvite { invite_id }).unwrap()
+    }
+
+    async fn decline_invite(&self, invite_id: u64) -> Vec<u8> {
+        bcs::to_bytes(&Operation::DeclineInvite { invite_id }).unwrap()
+    }
+
+    async fn record_practice(&self, keys: Vec<KeyStat>, bigrams: Vec<KeyStat>) -> Vec<u8> {
+        bcs::to_bytes(&Operation::RecordPractice { keys, bigrams }).unwrap()
+    }
+
+    async fn add_library_passage(&self, text: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::AddLibraryPassage { text }).unwrap()
+    }
+
+    async fn request_practice_passage(&self, lobby_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::RequestPracticePassage { lobby_chain_id }).unwrap()
+    }
+
+    async fn migrate_state(&self, batch_size: u32) -> Vec<u8> {
+        bcs::to_bytes(&Operation::MigrateState { batch_size }).unwrap()
+    }
+
+    async fn award_tournament(&self, tournament_id: String, winner: String) -> Vec<u8> {
+        bcs::to_bytes(&Operation::AwardTournament { tournament_id, winner }).unwrap()
+    }
+
+    async fn mint_badge(&self, achievement: Achievement, host_chain_id: ChainId) -> Vec<u8> {
+        bcs::to_bytes(&Operation::MintBadge { achievement, host_chain_id }).unwrap()
+    }
 }
 
 impl Service for TypeArena {
-    type Parameters = ();
+    type Parameters = TypeArenaParameters;
     
     async fn new(runtime: ServiceRuntime<Self>) -> Self {
         let state = TypeArenaState::load(runtime.root_view_storage_context())
@@ -0,0 +1,20 @@
+use linera_sdk::linera_base_types::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// A friend entry, with the chain invitations are delivered to.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct Friend {
+    pub player: String,
+    pub chain_id: ChainId,
+}
+
+/// A pending invitation to join a room.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct Invite {
+    pub id: u64,
+    pub from: String,
+    pub to: String,
+    pub room_id: String,
+    pub host_chain_id: ChainId,
+    pub received_at: u64,
+}
@@ -1,4 +1,21 @@
-use linera_sdk::views::{linera_views, MapView, RootView, ViewStorageContext};
+use crate::achievements::{self, Achievement, Badge};
+use crate::config::GameConfig;
+use crate::history::{PlayerChain, RaceRecord};
+use crate::moderation::{ModerationKind, ModerationRecord, RaceOutcome, ResultEffect, ResultTally};
+use crate::relay::{self, RelaySetup, RoomMode, SegmentResult, Team, TeamStanding};
+use crate::rewards::{self, RewardBudget, RewardPayout, RewardReason};
+use crate::betting::{MarketSettlement, RaceBet, RaceMarket};
+use crate::passage::{self, PassageInfo};
+use crate::practice::{self, KeyStat, LibraryPassage, PracticePassage, PracticeProfile};
+use crate::seasons::{self, SeasonArchive, SeasonStats};
+use crate::social::{Friend, Invite};
+use crate::submission::Submission;
+use crate::versioning::{
+    self, MigrationReport, PlayerStatsV1, RoomV1, TournamentV1, VersionedPlayerStats, VersionedRoom,
+    VersionedTournament,
+};
+use linera_sdk::linera_base_types::{Amount, ChainId};
+use linera_sdk::views::{linera_views, LogView, MapView, RegisterView, RootView, View, ViewStorageContext};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -7,6 +24,32 @@ pub enum StateError {
     RoomExists,
     RoomNotFound,
     RoomFinished,
+    AchievementLocked,
+    BadgeAlreadyMinted,
+    WrongRoomMode,
+    InvalidTeamSetup,
+    TeamNotFound,
+    TeamFull,
+    AlreadyOnTeam,
+    NotTeamMember,
+    SegmentLocked,
+    SeasonStillActive,
+    PlayerBanned,
+    PlayerNotBanned,
+    ResultNotFound,
+    ImplausibleWpm,
+    RaceDeadlinePassed,
+    RoomFull,
+    NotHost,
+    WrongPhase,
+    InvalidBet,
+    LanguageNotAllowed,
+    FriendNotFound,
+    InviteNotFound,
+    NotInvitee,
+    TournamentFinished,
+    KeystrokeProofRequired,
+    InvalidKeystrokeProof,
     ViewError(linera_sdk::views::ViewError),
 }
 
@@ -16,6 +59,32 @@ impl fmt::Display for StateError {
             StateError::RoomExists => write!(f, "Room already exists"),
             StateError::RoomNotFound => write!(f, "Room not found"),
             StateError::RoomFinished => write!(f, "Room already finished"),
+            StateError::AchievementLocked => write!(f, "Achievement not unlocked"),
+            StateError::BadgeAlreadyMinted => write!(f, "Badge already minted"),
+            StateError::WrongRoomMode => write!(f, "Operation not supported in this room mode"),
+            StateError::InvalidTeamSetup => write!(f, "Invalid relay team setup"),
+            StateError::TeamNotFound => write!(f, "Team not found"),
+            StateError::TeamFull => write!(f, "Team is full"),
+            StateError::AlreadyOnTeam => write!(f, "Player already on a team"),
+            StateError::NotTeamMember => write!(f, "Player is not on a team in this room"),
+            StateError::SegmentLocked => write!(f, "Previous segment not recorded yet"),
+            StateError::SeasonStillActive => write!(f, "Season has not ended yet"),
+            StateError::PlayerBanned => write!(f, "Player is banned"),
+            StateError::PlayerNotBanned => write!(f, "Player is not banned"),
+            StateError::ResultNotFound => write!(f, "Result not found"),
+            StateError::ImplausibleWpm => write!(f, "WPM above the plausibility threshold"),
+            StateError::RaceDeadlinePassed => write!(f, "Race deadline has passed"),
+            StateError::RoomFull => write!(f, "Room is full"),
+            StateError::NotHost => write!(f, "Only the room host can do this"),
+            StateError::WrongPhase => write!(f, "Room is not in the right phase"),
+            StateError::InvalidBet => write!(f, "Invalid spectator bet"),
+            StateError::LanguageNotAllowed => write!(f, "Passage language is not allowed"),
+            StateError::FriendNotFound => write!(f, "Friend not found"),
+            StateError::InviteNotFound => write!(f, "Invite not found"),
+            StateError::NotInvitee => write!(f, "Invite is addressed to another player"),
+            StateError::TournamentFinished => write!(f, "Tournament already has a winner"),
+            StateError::KeystrokeProofRequired => write!(f, "Results must include a keystroke proof"),
+            StateError::InvalidKeystrokeProof => write!(f, "Keystroke proof does not match the result"),
             StateError::ViewError(e) => write!(f, "View error: {}", e),
         }
     }
@@ -32,22 +101,320 @@ impl From<linera_sdk::views::ViewError> for StateError {
 #[derive(RootView, async_graphql::SimpleObject)]
 #[view(context = ViewStorageContext)]
 pub struct TypeArenaState {
-    pub rooms: MapView<String, Room>,
-    pub tournaments: MapView<String, Tournament>,
-    pub player_stats: MapView<String, PlayerStats>,
+    /// Unversioned records written before `versioning` existed. They must keep
+    /// the first three positions for existing data to stay readable.
+    #[graphql(skip)]
+    pub legacy_rooms: MapView<String, RoomV1>,
+    #[graphql(skip)]
+    pub legacy_tournaments: MapView<String, TournamentV1>,
+    #[graphql(skip)]
+    pub legacy_player_stats: MapView<String, PlayerStatsV1>,
+    pub achievements: MapView<String, Vec<Achievement>>,
+    pub badges: MapView<u64, Badge>,
+    pub player_badges: MapView<String, Vec<u64>>,
+    pub next_badge_id: RegisterView<u64>,
+    pub season: RegisterView<u32>,
+    pub season_started_at: RegisterView<u64>,
+    pub season_stats: MapView<String, SeasonStats>,
+    pub season_archive: MapView<u32, SeasonArchive>,
+    /// Banned players, mapped to the audit log entry that banned them.
+    pub banned: MapView<String, u64>,
+    pub audit_log: LogView<ModerationRecord>,
+    pub config: RegisterView<GameConfig>,
+    /// Finished races of players who race from this chain.
+    pub race_history: LogView<RaceRecord>,
+    /// Friend lists of players on this chain.
+    pub friends: MapView<String, Vec<Friend>>,
+    /// Pending room invitations received by players on this chain.
+    pub invites: MapView<u64, Invite>,
+    pub next_invite_id: RegisterView<u64>,
+    /// Practice statistics of players on this chain.
+    pub practice: MapView<String, PracticeProfile>,
+    /// The latest practice passage received for each player.
+    pub practice_passages: MapView<String, PracticePassage>,
+    /// Passages the lobby chain serves for practice.
+    pub passage_library: MapView<u64, LibraryPassage>,
+    pub next_passage_id: RegisterView<u64>,
+    /// Rooms, tournaments and player stats tagged with their layout version.
+    /// Use the accessors below, which upgrade older records on read.
+    #[graphql(skip)]
+    pub rooms: MapView<String, VersionedRoom>,
+    #[graphql(skip)]
+    pub tournaments: MapView<String, VersionedTournament>,
+    #[graphql(skip)]
+    pub player_stats: MapView<String, VersionedPlayerStats>,
+    /// The layout version all records were last migrated to.
+    pub schema_version: RegisterView<u32>,
+    /// Every reward paid from the treasury, oldest first.
+    pub reward_ledger: LogView<RewardPayout>,
+    pub reward_budget: RegisterView<RewardBudget>,
+    /// Positions in `race_history` of each player's races, oldest first.
+    pub race_index: MapView<String, Vec<u32>>,
+    /// What each room's results added to player stats, so they can be invalidated.
+    #[graphql(skip)]
+    pub result_effects: MapView<String, Vec<ResultEffect>>,
+    #[graphql(skip)]
+    pub result_tallies: MapView<String, ResultTally>,
+    /// Rewards paid for invalidated results, withheld from the player's next rewards.
+    pub reward_debts: MapView<String, Amount>,
+    /// Ids of finished rooms in the order they finished, for exports. Deleted
+    /// rooms keep their entry so later offsets stay valid.
+    pub finished_rooms: LogView<String>,
+    /// Positions in `reward_ledger` of each player's payouts, oldest first.
+    pub reward_index: MapView<String, Vec<u32>>,
+    /// The chain each player last raced from, for rewards not tied to a room.
+    pub last_chains: MapView<String, ChainId>,
 }
 
 impl TypeArenaState {
+    pub async fn room(&self, room_id: &str) -> Result<Option<Room>, StateError> {
+        if let Some(room) = self.rooms.get(room_id).await? {
+            return Ok(Some(room.into_latest()));
+        }
+        Ok(self.legacy_rooms.get(room_id).await?.map(|room| VersionedRoom::V1(room).into_latest()))
+    }
+
+    pub async fn has_room(&self, room_id: &str) -> Result<bool, StateError> {
+        Ok(self.rooms.contains_key(room_id).await? || self.legacy_rooms.contains_key(room_id).await?)
+    }
+
+    /// Stores a room at the latest layout, dropping any legacy copy. A legacy room
+    /// that had already finished joins `finished_rooms`, which it predates.
+    pub async fn put_room(&mut self, room_id: &str, room: Room) -> Result<(), StateError> {
+        let legacy = match self.rooms.get(room_id).await? {
+            Some(stored) if !stored.is_latest() => Some(stored.into_latest()),
+            Some(_) => None,
+            None => self.legacy_rooms.get(room_id).await?.map(|room| VersionedRoom::V1(room).into_latest()),
+        };
+        if legacy.is_some_and(|legacy| legacy.is_finished) {
+            self.finished_rooms.push(room_id.to_string());
+        }
+        self.legacy_rooms.remove(room_id)?;
+        self.rooms.insert(room_id, VersionedRoom::from(room))?;
+        Ok(())
+    }
+
+    pub fn remove_room(&mut self, room_id: &str) -> Result<(), StateError> {
+        self.legacy_rooms.remove(room_id)?;
+        self.rooms.remove(room_id)?;
+        Ok(())
+    }
+
+    /// Visits every room at the latest layout.
+    pub async fn for_each_room(&self, mut f: impl FnMut(Room) + Send) -> Result<(), StateError> {
+        self.rooms
+            .for_each_index_value(|_, room| {
+                f(room.into_owned().into_latest());
+                Ok(())
+            })
+            .await?;
+        self.legacy_rooms
+            .for_each_index_value(|_, room| {
+                f(VersionedRoom::V1(room.into_owned()).into_latest());
+                Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Finished rooms at positions `offset..offset + limit` of `finished_rooms`,
+    /// skipping deleted ones, and the offset of the next page if there is one.
+    pub async fn finished_room_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<Room>, Option<usize>), StateError> {
+        let count = self.finished_rooms.count();
+        let start = offset.min(count);
+        let end = count.min(start.saturating_add(limit));
+        let mut rooms = Vec::new();
+        for room_id in self.finished_rooms.read(start..end).await? {
+            if let Some(room) = self.room(&room_id).await? {
+                rooms.push(room);
+            }
+        }
+        Ok((rooms, (end < count).then_some(end)))
+    }
+
+    /// Players with all-time stats at positions `offset..offset + limit`, in storage
+    /// order with legacy records last, and the offset of the next page if there is
+    /// one. Keys past the end of the page are not read.
+    pub async fn players_with_stats_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<usize>), StateError> {
+        let end = offset.saturating_add(limit);
+        let mut position = 0;
+        let mut players = Vec::new();
+        let mut visit = |player: String| {
+            if position < end && position >= offset {
+                players.push(player);
+            }
+            position += 1;
+            Ok(position <= end)
+        };
+        self.player_stats.for_each_index_while(&mut visit).await?;
+        // Stops at the first key if the page is already full.
+        self.legacy_player_stats.for_each_index_while(&mut visit).await?;
+        Ok((players, (position > end).then_some(end)))
+    }
+
+    pub async fn tournament(&self, tournament_id: &str) -> Result<Option<Tournament>, StateError> {
+        if let Some(tournament) = self.tournaments.get(tournament_id).await? {
+            return Ok(Some(tournament.into_latest()));
+        }
+        Ok(self
+            .legacy_tournaments
+            .get(tournament_id)
+            .await?
+            .map(|tournament| VersionedTournament::V1(tournament).into_latest()))
+    }
+
+    pub fn put_tournament(&mut self, tournament_id: &str, tournament: Tournament) -> Result<(), StateError> {
+        self.legacy_tournaments.remove(tournament_id)?;
+        self.tournaments.insert(tournament_id, VersionedTournament::from(tournament))?;
+        Ok(())
+    }
+
+    pub async fn stats(&self, player: &str) -> Result<Option<PlayerStats>, StateError> {
+        if let Some(stats) = self.player_stats.get(player).await? {
+            return Ok(Some(stats.into_latest()));
+        }
+        Ok(self
+            .legacy_player_stats
+            .get(player)
+            .await?
+            .map(|stats| VersionedPlayerStats::V1(stats).into_latest()))
+    }
+
+    pub fn put_stats(&mut self, player: &str, stats: PlayerStats) -> Result<(), StateError> {
+        self.legacy_player_stats.remove(player)?;
+        self.player_stats.insert(player, VersionedPlayerStats::from(stats))?;
+        Ok(())
+    }
+
+    /// Rewrites up to `limit` records that are not at `SCHEMA_VERSION`, rooms first.
+    /// Once nothing is left, the schema version is bumped.
+    pub async fn migrate(&mut self, limit: u32) -> Result<MigrationReport, StateError> {
+        let mut report = MigrationReport::default();
+        let mut budget = limit as usize;
+
+        let mut room_ids = self.legacy_rooms.indices().await?;
+        self.rooms
+            .for_each_index_value(|room_id, room| {
+                if !room.is_latest() {
+                    room_ids.push(room_id);
+                }
+                Ok(())
+            })
+            .await?;
+        let mut tournament_ids = self.legacy_tournaments.indices().await?;
+        self.tournaments
+            .for_each_index_value(|tournament_id, tournament| {
+                if !tournament.is_latest() {
+                    tournament_ids.push(tournament_id);
+                }
+                Ok(())
+            })
+            .await?;
+        let mut players = self.legacy_player_stats.indices().await?;
+        self.player_stats
+            .for_each_index_value(|player, stats| {
+                if !stats.is_latest() {
+                    players.push(player);
+                }
+                Ok(())
+            })
+            .await?;
+        let pending = room_ids.len() + tournament_ids.len() + players.len();
+
+        for room_id in room_ids.into_iter().take(budget) {
+            if let Some(room) = self.room(&room_id).await? {
+                self.put_room(&room_id, room).await?;
+            }
+            report.rooms += 1;
+        }
+        budget -= report.rooms as usize;
+        for tournament_id in tournament_ids.into_iter().take(budget) {
+            if let Some(tournament) = self.tournament(&tournament_id).await? {
+                self.put_tournament(&tournament_id, tournament)?;
+            }
+            report.tournaments += 1;
+        }
+        budget -= report.tournaments as usize;
+        for player in players.into_iter().take(budget) {
+            if let Some(stats) = self.stats(&player).await? {
+                self.put_stats(&player, stats)?;
+            }
+            report.player_stats += 1;
+        }
+
+        let migrated = (report.rooms + report.tournaments + report.player_stats) as usize;
+        report.complete = migrated == pending;
+        if report.complete {
+            self.schema_version.set(versioning::SCHEMA_VERSION);
+        }
+        Ok(report)
+    }
+
     pub async fn create_room(
         &mut self,
         room_id: String,
         host: String,
         text: String,
+        passage: Option<PassageInfo>,
+        start_time: u64,
+    ) -> Result<(), StateError> {
+        if self.has_room(&room_id).await? {
+            return Err(StateError::RoomExists);
+        }
+        self.check_language(passage.as_ref())?;
+        let room = Room {
+            id: room_id.clone(),
+            host,
+            text,
+            start_time: Some(start_time),
+            end_time: None,
+            players: vec![],
+            participants: vec![],
+            is_finished: false,
+            max_players: self.config.get().default_room_size,
+            mode: RoomMode::Solo,
+            segments: vec![],
+            teams: vec![],
+            player_chains: vec![],
+            passage,
+            phase: RoomPhase::Lobby,
+            market: None,
+        };
+        self.put_room(&room_id, room).await?;
+        Ok(())
+    }
+
+    /// Creates a relay room with `setup.team_count` teams of `setup.team_size`
+    /// members each. The passage is split into one segment per team member.
+    pub async fn create_relay_room(
+        &mut self,
+        room_id: String,
+        host: String,
+        text: String,
+        passage: Option<PassageInfo>,
         start_time: u64,
+        setup: RelaySetup,
     ) -> Result<(), StateError> {
-        if self.rooms.contains_key(&room_id).await? {
+        let RelaySetup { team_count, team_size } = setup;
+        if self.has_room(&room_id).await? {
             return Err(StateError::RoomExists);
         }
+        self.check_language(passage.as_ref())?;
+        if team_count < 2 {
+            return Err(StateError::InvalidTeamSetup);
+        }
+        let segments = relay::split_passage(&text, team_size).ok_or(StateError::InvalidTeamSetup)?;
+        let teams = (0..team_count)
+            .map(|index| Team { index, ..Team::default() })
+            .collect();
         let room = Room {
             id: room_id.clone(),
             host,
@@ -57,59 +424,854 @@ impl TypeArenaState {
             players: vec![],
             participants: vec![],
             is_finished: false,
+            max_players: team_count.saturating_mul(team_size),
+            mode: RoomMode::Relay,
+            segments,
+            teams,
+            player_chains: vec![],
+            passage,
+            phase: RoomPhase::Lobby,
+            market: None,
         };
-        self.rooms.insert(&room_id, room)?;
+        self.put_room(&room_id, room).await?;
         Ok(())
     }
 
     pub async fn join_room(&mut self, room_id: String, player: String) -> Result<(), StateError> {
-        let mut room = self.rooms.get(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if self.banned.contains_key(&player).await? {
+            return Err(StateError::PlayerBanned);
+        }
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
         if room.is_finished {
              return Err(StateError::RoomFinished);
         }
         if !room.participants.contains(&player) {
+            if room.max_players > 0 && room.participants.len() >= room.max_players as usize {
+                return Err(StateError::RoomFull);
+            }
             room.participants.push(player);
-            self.rooms.insert(&room_id, room)?;
+            self.put_room(&room_id, room).await?;
         }
         Ok(())
     }
 
+    pub async fn join_team(&mut self, room_id: String, team: u32, player: String) -> Result<(), StateError> {
+        if self.banned.contains_key(&player).await? {
+            return Err(StateError::PlayerBanned);
+        }
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if room.is_finished {
+             return Err(StateError::RoomFinished);
+        }
+        if room.mode != RoomMode::Relay {
+            return Err(StateError::WrongRoomMode);
+        }
+        if room.team_of(&player).is_some() {
+            return Err(StateError::AlreadyOnTeam);
+        }
+        let team_size = room.segments.len();
+        let team = room.teams.get_mut(team as usize).ok_or(StateError::TeamNotFound)?;
+        if team.members.len() >= team_size {
+            return Err(StateError::TeamFull);
+        }
+        team.members.push(player.clone());
+        if !room.participants.contains(&player) {
+            room.participants.push(player);
+        }
+        self.put_room(&room_id, room).await?;
+        Ok(())
+    }
+
+    /// Records the player's relay segment. A member may only submit once every
+    /// teammate before them has a recorded segment.
+    pub async fn submit_segment(
+        &mut self,
+        room_id: String,
+        player: String,
+        submission: Submission,
+        now: u64,
+    ) -> Result<(u32, SegmentResult, Vec<Achievement>), StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if room.is_finished {
+             return Err(StateError::RoomFinished);
+        }
+        if room.mode != RoomMode::Relay {
+            return Err(StateError::WrongRoomMode);
+        }
+        let team_index = room.team_of(&player).ok_or(StateError::NotTeamMember)?;
+        let team = &room.teams[team_index];
+        let segment = team
+            .members
+            .iter()
+            .position(|member| *member == player)
+            .expect("team_of returned the player's team") as u32;
+        // Segments are only accepted in order, right after all earlier ones. Once a
+        // teammate's segment is invalidated, members after them who already raced
+        // cannot race again, so the team cannot finish.
+        if !team.segment_results.iter().map(|result| result.segment).eq(0..segment) {
+            return Err(StateError::SegmentLocked);
+        }
+        let text = &room.segments[segment as usize];
+        self.check_proof(text, &submission)?;
+        let Submission { wpm, time_ms, .. } = submission;
+        let wpm = room.scored_wpm(text, wpm, time_ms);
+        self.check_result_rules(&room, wpm, now)?;
+        let team = &mut room.teams[team_index];
+        let result = SegmentResult {
+            segment,
+            player: player.clone(),
+            wpm,
+            time_ms,
+        };
+        team.segment_results.push(result.clone());
+        let team_index = team.index;
+        self.put_room(&room_id, room).await?;
+
+        let unlocked = self.record_race(&room_id, &player, wpm).await?;
+        Ok((team_index, result, unlocked))
+    }
+
     pub async fn submit_result(
         &mut self,
         room_id: String,
         player: String,
-        wpm: u32,
-        time_ms: u64,
-    ) -> Result<(), StateError> {
-        let mut room = self.rooms.get(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        submission: Submission,
+        accuracy: Option<u32>,
+        now: u64,
+    ) -> Result<Vec<Achievement>, StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
         if room.is_finished {
              return Err(StateError::RoomFinished);
         }
+        if room.mode != RoomMode::Solo {
+            return Err(StateError::WrongRoomMode);
+        }
+        self.check_proof(&room.text, &submission)?;
+        let Submission { wpm, time_ms, .. } = submission;
+        let wpm = room.scored_wpm(&room.text, wpm, time_ms);
+        self.check_result_rules(&room, wpm, now)?;
         
         if !room.players.iter().any(|p| p.address == player) {
              room.players.push(PlayerResult {
                  address: player.clone(),
                  wpm,
                  finish_time_ms: time_ms,
+                 accuracy,
              });
-             self.rooms.insert(&room_id, room)?;
+             self.put_room(&room_id, room).await?;
+             return self.record_race(&room_id, &player, wpm).await;
+        }
+        Ok(vec![])
+    }
 
-             let mut stats = self.player_stats.get(&player).await?.unwrap_or_default();
-             stats.total_races += 1;
-             if wpm > stats.best_wpm {
-                 stats.best_wpm = wpm;
-             }
-             self.player_stats.insert(&player, stats)?;
+    /// Moves a room from the lobby into its countdown. A spectator bet is only
+    /// allowed on solo rooms.
+    pub async fn start_countdown(
+        &mut self,
+        room_id: String,
+        caller: &str,
+        bet: Option<&RaceBet>,
+    ) -> Result<(), StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if room.is_finished {
+            return Err(StateError::RoomFinished);
         }
+        if room.host != caller {
+            return Err(StateError::NotHost);
+        }
+        if room.phase != RoomPhase::Lobby {
+            return Err(StateError::WrongPhase);
+        }
+        if let Some(bet) = bet {
+            if room.mode != RoomMode::Solo {
+                return Err(StateError::WrongRoomMode);
+            }
+            if !bet.is_valid() {
+                return Err(StateError::InvalidBet);
+            }
+        }
+        room.phase = RoomPhase::Countdown;
+        self.put_room(&room_id, room).await?;
         Ok(())
     }
 
-    pub async fn finish_room(&mut self, room_id: String) -> Result<(), StateError> {
-        let mut room = self.rooms.get(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+    pub async fn attach_market(&mut self, room_id: String, market: RaceMarket) -> Result<(), StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        room.market = Some(market);
+        self.put_room(&room_id, room).await?;
+        Ok(())
+    }
+
+    /// Starts the race after the countdown, resetting the start time the result
+    /// deadline counts from. Returns the room's market, whose betting must now lock.
+    pub async fn start_race(
+        &mut self,
+        room_id: String,
+        caller: &str,
+        now: u64,
+    ) -> Result<Option<u64>, StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if room.is_finished {
+            return Err(StateError::RoomFinished);
+        }
+        if room.host != caller {
+            return Err(StateError::NotHost);
+        }
+        if room.phase != RoomPhase::Countdown {
+            return Err(StateError::WrongPhase);
+        }
+        room.phase = RoomPhase::Racing;
+        room.start_time = Some(now);
+        let market_id = room.market.as_ref().map(|market| market.market_id);
+        self.put_room(&room_id, room).await?;
+        Ok(market_id)
+    }
+
+    /// Remembers which chain a player races from, so the room's result can be
+    /// confirmed back to that chain when it finishes and later rewards paid to it.
+    pub async fn record_player_chain(
+        &mut self,
+        room_id: String,
+        player: String,
+        chain_id: ChainId,
+    ) -> Result<(), StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        self.last_chains.insert(&player, chain_id)?;
+        match room.player_chains.iter_mut().find(|entry| entry.player == player) {
+            Some(entry) if entry.chain_id == chain_id => return Ok(()),
+            Some(entry) => entry.chain_id = chain_id,
+            None => room.player_chains.push(PlayerChain { player, chain_id }),
+        }
+        self.put_room(&room_id, room).await?;
+        Ok(())
+    }
+
+    /// The chain the player last raced from, if they raced on this chain.
+    pub async fn player_chain(&self, player: &str) -> Result<Option<ChainId>, StateError> {
+        Ok(self.last_chains.get(player).await?)
+    }
+
+    /// Appends a finished race to this chain's history log and the player's index.
+    pub async fn record_history(&mut self, record: RaceRecord) -> Result<(), StateError> {
+        let mut positions = self.race_index.get(&record.player).await?.unwrap_or_default();
+        positions.push(self.race_history.count() as u32);
+        self.race_index.insert(&record.player, positions)?;
+        self.race_history.push(record);
+        Ok(())
+    }
+
+    /// Adds or updates a friend entry.
+    pub async fn add_friend(&mut self, player: String, friend: Friend) -> Result<(), StateError> {
+        let mut friends = self.friends.get(&player).await?.unwrap_or_default();
+        match friends.iter_mut().find(|entry| entry.player == friend.player) {
+            Some(entry) => entry.chain_id = friend.chain_id,
+            None => friends.push(friend),
+        }
+        self.friends.insert(&player, friends)?;
+        Ok(())
+    }
+
+    pub async fn remove_friend(&mut self, player: String, friend: String) -> Result<(), StateError> {
+        let mut friends = self.friends.get(&player).await?.unwrap_or_default();
+        let position = friends
+            .iter()
+            .position(|entry| entry.player == friend)
+            .ok_or(StateError::FriendNotFound)?;
+        friends.remove(position);
+        self.friends.insert(&player, friends)?;
+        Ok(())
+    }
+
+    pub async fn friend(&self, player: &str, friend: &str) -> Result<Friend, StateError> {
+        self.friends
+            .get(player)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .find(|entry| entry.player == friend)
+            .ok_or(StateError::FriendNotFound)
+    }
+
+    /// Stores an incoming invitation as pending.
+    pub fn receive_invite(
+        &mut self,
+        from: String,
+        to: String,
+        room_id: String,
+        host_chain_id: ChainId,
+        received_at: u64,
+    ) -> Result<Invite, StateError> {
+        let id = *self.next_invite_id.get();
+        self.next_invite_id.set(id + 1);
+        let invite = Invite {
+            id,
+            from,
+            to,
+            room_id,
+            host_chain_id,
+            received_at,
+        };
+        self.invites.insert(&id, invite.clone())?;
+        Ok(invite)
+    }
+
+    /// Removes a pending invitation addressed to `player`, for accepting or declining.
+    pub async fn take_invite(&mut self, invite_id: u64, player: &str) -> Result<Invite, StateError> {
+        let invite = self.invites.get(&invite_id).await?.ok_or(StateError::InviteNotFound)?;
+        if invite.to != player {
+            return Err(StateError::NotInvitee);
+        }
+        self.invites.remove(&invite_id)?;
+        Ok(invite)
+    }
+
+    pub async fn record_practice(
+        &mut self,
+        player: String,
+        keys: Vec<KeyStat>,
+        bigrams: Vec<KeyStat>,
+    ) -> Result<(), StateError> {
+        let mut profile = self.practice.get(&player).await?.unwrap_or_default();
+        profile.record(keys, bigrams);
+        self.practice.insert(&player, profile)?;
+        Ok(())
+    }
+
+    /// The player's weakest bigrams, for requesting a practice passage.
+    pub async fn focus_bigrams(&self, player: &str) -> Result<Vec<String>, StateError> {
+        let profile = self.practice.get(player).await?.unwrap_or_default();
+        Ok(practice::weakest(&profile.bigrams, practice::FOCUS_BIGRAMS)
+            .into_iter()
+            .map(|stat| stat.key)
+            .collect())
+    }
+
+    pub fn add_library_passage(&mut self, text: String) -> Result<u64, StateError> {
+        let id = *self.next_passage_id.get();
+        self.next_passage_id.set(id + 1);
+        self.passage_library.insert(&id, LibraryPassage { id, text })?;
+        Ok(id)
+    }
+
+    /// The library passage that best drills `focus`, ties going to the oldest.
+    pub async fn pick_library_passage(&self, focus: &[String]) -> Result<Option<LibraryPassage>, StateError> {
+        let mut best: Option<(u64, LibraryPassage)> = None;
+        self.passage_library
+            .for_each_index_value(|_, passage| {
+                let score = practice::focus_score(&passage.text, focus);
+                if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+                    best = Some((score, passage.into_owned()));
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(best.map(|(_, passage)| passage))
+    }
+
+    /// Rejects passages whose language is not in the configured allow-list.
+    /// Rooms without passage info count as `passage::DEFAULT_LANGUAGE`.
+    fn check_language(&self, info: Option<&PassageInfo>) -> Result<(), StateError> {
+        let language = info.map_or(passage::DEFAULT_LANGUAGE, |info| info.language.as_str());
+        if !self.config.get().allows_language(language) {
+            return Err(StateError::LanguageNotAllowed);
+        }
+        Ok(())
+    }
+
+    /// Applies the configured plausibility and deadline rules to a result, and
+    /// only accepts results for rooms with a market once the race has started.
+    fn check_result_rules(&self, room: &Room, wpm: u32, now: u64) -> Result<(), StateError> {
+        // Results posted before betting locks could be used to bet on a known outcome.
+        if room.market.is_some() && room.phase != RoomPhase::Racing {
+            return Err(StateError::WrongPhase);
+        }
+        let config = self.config.get();
+        if !config.is_plausible_wpm(wpm) {
+            return Err(StateError::ImplausibleWpm);
+        }
+        if !config.within_deadline(room.start_time.unwrap_or_default(), now) {
+            return Err(StateError::RaceDeadlinePassed);
+        }
+        Ok(())
+    }
+
+    /// Checks a submission's keystroke proof against the typed `text`. A missing
+    /// proof is only rejected when the config requires one.
+    fn check_proof(&self, text: &str, submission: &Submission) -> Result<(), StateError> {
+        match &submission.proof {
+            Some(proof) if !proof.supports(text, submission.time_ms) => Err(StateError::InvalidKeystrokeProof),
+            None if self.config.get().require_keystroke_proofs => Err(StateError::KeystrokeProofRequired),
+            _ => Ok(()),
+        }
+    }
+
+    async fn record_race(&mut self, room_id: &str, player: &str, wpm: u32) -> Result<Vec<Achievement>, StateError> {
+        let key = player.to_string();
+        let mut stats = self.stats(&key).await?.unwrap_or_default();
+        stats.total_races += 1;
+        if wpm > stats.best_wpm {
+            stats.best_wpm = wpm;
+        }
+        self.put_stats(&key, stats.clone())?;
+
+        let mut season_stats = self.season_stats.get(&key).await?.unwrap_or_default();
+        season_stats.total_races += 1;
+        season_stats.best_wpm = season_stats.best_wpm.max(wpm);
+        self.season_stats.insert(&key, season_stats)?;
+
+        let season = *self.season.get();
+        let mut tally = self.result_tallies.get(&key).await?.unwrap_or_default();
+        tally.add(wpm, season);
+        self.result_tallies.insert(&key, tally)?;
+        let mut effects = self.result_effects.get(room_id).await?.unwrap_or_default();
+        effects.push(ResultEffect { player: key, wpm, season, ..ResultEffect::default() });
+        self.result_effects.insert(room_id, effects)?;
+
+        self.unlock_achievements(player, &stats).await
+    }
+
+    /// Marks the room finished and credits the win to the fastest finisher, or
+    /// to every member of the fastest relay team. Only the host or an admin may
+    /// finish a room.
+    pub async fn finish_room(
+        &mut self,
+        room_id: String,
+        caller: &str,
+        admin: bool,
+        now: u64,
+    ) -> Result<FinishedRoom, StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if room.is_finished {
+             return Err(StateError::RoomFinished);
+        }
+        if room.host != caller && !admin {
+            return Err(StateError::NotHost);
+        }
         room.is_finished = true;
-        self.rooms.insert(&room_id, room)?;
+        let segment_count = room.segments.len() as u32;
+        let team_standings = relay::standings(&room.teams, segment_count);
+        // Players who actually raced with their WPM and accuracy, grouped by final place.
+        let (ranking, has_winner): (Vec<Vec<RankedRacer>>, bool) = match room.mode {
+            RoomMode::Solo => (
+                room.ranked_results()
+                    .into_iter()
+                    .map(|result| {
+                        vec![RankedRacer {
+                            player: result.address.clone(),
+                            wpm: result.wpm,
+                            accuracy: result.accuracy,
+                        }]
+                    })
+                    .collect(),
+                !room.players.is_empty(),
+            ),
+            RoomMode::Relay => (
+                team_standings
+                    .iter()
+                    .filter(|standing| standing.segments_completed > 0)
+                    .map(|standing| {
+                        room.teams[standing.team as usize]
+                            .segment_results
+                            .iter()
+                            .map(|result| RankedRacer {
+                                player: result.player.clone(),
+                                wpm: result.wpm,
+                                accuracy: None,
+                            })
+                            .collect()
+                    })
+                    .collect(),
+                team_standings.first().is_some_and(|standing| standing.finished),
+            ),
+        };
+        let market = room.market.as_ref().map(|market| match room.phase {
+            RoomPhase::Racing => MarketSettlement::Resolve {
+                market_id: market.market_id,
+                came_true: market.bet.outcome(&room.ranked_results()),
+            },
+            _ => MarketSettlement::Cancel { market_id: market.market_id },
+        });
+        let finishers = ranking.iter().map(Vec::len).sum::<usize>();
+        let rewarded = room.phase == RoomPhase::Racing && finishers >= rewards::MIN_REWARDED_FINISHERS;
+        let passage = room.text.clone();
+        let player_chains = room.player_chains.clone();
+        self.put_room(&room_id, room).await?;
+        self.finished_rooms.push(room_id.clone());
+
+        let mut effects = self.result_effects.get(&room_id).await?.unwrap_or_default();
+        let mut unlocked = vec![];
+        let mut records = vec![];
+        for (place, group) in ranking.iter().enumerate() {
+            let won = has_winner && place == 0;
+            let rating_delta = seasons::rating_delta(place, ranking.len());
+            for RankedRacer { player, wpm, accuracy } in group {
+                let mut stats = self.stats(player).await?.unwrap_or_default();
+                let best_win_streak_before = stats.best_win_streak;
+                if won {
+                    stats.wins += 1;
+                    stats.win_streak += 1;
+                    stats.best_win_streak = stats.best_win_streak.max(stats.win_streak);
+                } else {
+                    stats.win_streak = 0;
+                }
+                self.put_stats(player, stats.clone())?;
+
+                let mut season_stats = self.season_stats.get(player).await?.unwrap_or_default();
+                if won {
+                    season_stats.wins += 1;
+                }
+                season_stats.apply_rating_delta(rating_delta);
+                self.season_stats.insert(player, season_stats)?;
+
+                let mut tally = self.result_tallies.get(player).await?.unwrap_or_default();
+                tally.ranked += 1;
+                if !won {
+                    tally.last_loss = tally.ranked;
+                }
+                if let Some(effect) = effects.iter_mut().find(|effect| effect.player == *player) {
+                    effect.outcome = Some(RaceOutcome { race: tally.ranked, won, rating_delta, best_win_streak_before });
+                }
+                self.result_tallies.insert(player, tally)?;
+
+                for achievement in self.unlock_achievements(player, &stats).await? {
+                    unlocked.push((player.clone(), achievement));
+                }
+
+                let chain_id = player_chains
+                    .iter()
+                    .find(|entry| entry.player == *player)
+                    .map(|entry| entry.chain_id);
+                let record = RaceRecord {
+                    player: player.clone(),
+                    room_id: room_id.clone(),
+                    passage: passage.clone(),
+                    placement: place as u32 + 1,
+                    wpm: *wpm,
+                    accuracy: *accuracy,
+                    timestamp: now,
+                };
+                records.push((chain_id, record));
+            }
+        }
+        self.result_effects.insert(&room_id, effects)?;
+        Ok(FinishedRoom { team_standings, unlocked, records, market, rewarded })
+    }
+
+    /// Starts the first season. Called once when the application is instantiated.
+    pub fn start_first_season(&mut self, now: u64) {
+        self.season.set(0);
+        self.season_started_at.set(now);
+    }
+
+    /// Archives the current season's top standings, resets season stats and
+    /// opens the next season. Unless `early` is set, fails if the season has not
+    /// run its full length.
+    pub async fn close_season(&mut self, now: u64, early: bool) -> Result<SeasonArchive, StateError> {
+        let started_at = *self.season_started_at.get();
+        if !early && now < started_at.saturating_add(seasons::SEASON_LENGTH_MICROS) {
+            return Err(StateError::SeasonStillActive);
+        }
+        let season = *self.season.get();
+        let entries = self.season_stats.index_values().await?;
+        let archive = SeasonArchive {
+            season,
+            started_at,
+            closed_at: now,
+            standings: seasons::top_standings(entries, seasons::SEASON_ARCHIVE_SIZE),
+        };
+        self.season_archive.insert(&season, archive.clone())?;
+        self.season_stats.clear();
+        self.season.set(season + 1);
+        self.season_started_at.set(now);
+        Ok(archive)
+    }
+
+    /// Records a tournament's winner, creating the tournament if this chain has no
+    /// record of it. Returns achievements the win unlocked.
+    pub async fn award_tournament(
+        &mut self,
+        tournament_id: String,
+        winner: String,
+        host: String,
+    ) -> Result<Vec<Achievement>, StateError> {
+        let mut tournament = self.tournament(&tournament_id).await?.unwrap_or_else(|| Tournament {
+            id: tournament_id.clone(),
+            host,
+            participants: vec![winner.clone()],
+            ..Tournament::default()
+        });
+        if tournament.winner.is_some() {
+            return Err(StateError::TournamentFinished);
+        }
+        tournament.winner = Some(winner.clone());
+        self.put_tournament(&tournament_id, tournament)?;
+
+        let mut stats = self.stats(&winner).await?.unwrap_or_default();
+        stats.tournaments_won += 1;
+        self.put_stats(&winner, stats.clone())?;
+        self.unlock_achievements(&winner, &stats).await
+    }
+
+    /// Appends a payout to the reward ledger and counts it against the day's budget.
+    /// A placement reward is also kept with the result, in case it is invalidated.
+    pub async fn record_reward(&mut self, payout: RewardPayout) -> Result<(), StateError> {
+        self.reward_budget.get_mut().spend(payout.paid_at, payout.amount);
+        if payout.reason == RewardReason::RacePlacement {
+            let mut effects = self.result_effects.get(&payout.reference).await?.unwrap_or_default();
+            if let Some(effect) = effects.iter_mut().find(|effect| effect.player == payout.player) {
+                effect.reward = effect.reward.saturating_add(payout.amount);
+                self.result_effects.insert(&payout.reference, effects)?;
+            }
+        }
+        let mut positions = self.reward_index.get(&payout.player).await?.unwrap_or_default();
+        positions.push(self.reward_ledger.count() as u32);
+        self.reward_index.insert(&payout.player, positions)?;
+        self.reward_ledger.push(payout);
+        Ok(())
+    }
+
+    /// What the player still owes for rewards paid on invalidated results.
+    pub async fn reward_debt(&self, player: &str) -> Result<Amount, StateError> {
+        Ok(self.reward_debts.get(player).await?.unwrap_or_default())
+    }
+
+    /// Reduces the player's reward debt by `amount` withheld from a reward.
+    pub async fn settle_reward_debt(&mut self, player: &str, amount: Amount) -> Result<(), StateError> {
+        let debt = self.reward_debt(player).await?.saturating_sub(amount);
+        if debt == Amount::ZERO {
+            self.reward_debts.remove(player)?;
+        } else {
+            self.reward_debts.insert(player, debt)?;
+        }
+        Ok(())
+    }
+
+    /// Appends an entry to the moderation audit log.
+    pub fn log_moderation(
+        &mut self,
+        admin: String,
+        kind: ModerationKind,
+        player: Option<String>,
+        room_id: Option<String>,
+        reason: String,
+        timestamp: u64,
+    ) -> ModerationRecord {
+        let record = ModerationRecord {
+            id: self.audit_log.count() as u64,
+            admin,
+            kind,
+            player,
+            room_id,
+            reason,
+            timestamp,
+        };
+        self.audit_log.push(record.clone());
+        record
+    }
+
+    pub async fn ban_player(
+        &mut self,
+        admin: String,
+        player: String,
+        reason: String,
+        timestamp: u64,
+    ) -> Result<ModerationRecord, StateError> {
+        if self.banned.contains_key(&player).await? {
+            return Err(StateError::PlayerBanned);
+        }
+        let record = self.log_moderation(admin, ModerationKind::BanPlayer, Some(player.clone()), None, reason, timestamp);
+        self.banned.insert(&player, record.id)?;
+        Ok(record)
+    }
+
+    pub async fn unban_player(
+        &mut self,
+        admin: String,
+        player: String,
+        reason: String,
+        timestamp: u64,
+    ) -> Result<ModerationRecord, StateError> {
+        if !self.banned.contains_key(&player).await? {
+            return Err(StateError::PlayerNotBanned);
+        }
+        self.banned.remove(&player)?;
+        Ok(self.log_moderation(admin, ModerationKind::UnbanPlayer, Some(player), None, reason, timestamp))
+    }
+
+    /// Removes a player's solo result or relay segment from a room and reverts what
+    /// it added: all-time stats, current-season stats and rating, and any placement
+    /// reward, which is withheld from the player's later rewards. A win in the current
+    /// streak is taken out of it; losses and wins from earlier streaks leave streaks
+    /// as they are. Unlocked achievements are kept, as are archived seasons.
+    pub async fn invalidate_result(
+        &mut self,
+        admin: String,
+        room_id: String,
+        player: String,
+        reason: String,
+        timestamp: u64,
+    ) -> Result<InvalidatedResult, StateError> {
+        let mut room = self.room(&room_id).await?.ok_or(StateError::RoomNotFound)?;
+        if let Some(position) = room.players.iter().position(|p| p.address == player) {
+            room.players.remove(position);
+        } else {
+            let team = room
+                .teams
+                .iter_mut()
+                .find(|team| team.segment_results.iter().any(|result| result.player == player))
+                .ok_or(StateError::ResultNotFound)?;
+            team.segment_results.retain(|result| result.player != player);
+        }
+        let confirmed = room.is_finished;
+        let chain_id = room
+            .player_chains
+            .iter()
+            .find(|entry| entry.player == player)
+            .map(|entry| entry.chain_id);
+        self.put_room(&room_id, room).await?;
+
+        let mut effects = self.result_effects.get(&room_id).await?.unwrap_or_default();
+        if let Some(position) = effects.iter().position(|effect| effect.player == player) {
+            let effect = effects.remove(position);
+            self.result_effects.insert(&room_id, effects)?;
+            self.revert_effect(effect).await?;
+        }
+
+        let record = self.log_moderation(admin, ModerationKind::InvalidateResult, Some(player), Some(room_id), reason, timestamp);
+        Ok(InvalidatedResult { record, confirmed, chain_id })
+    }
+
+    /// Takes back what one result added to its player's stats.
+    async fn revert_effect(&mut self, effect: ResultEffect) -> Result<(), StateError> {
+        let player = effect.player;
+        let mut tally = self.result_tallies.get(&player).await?.unwrap_or_default();
+        tally.remove(effect.wpm, effect.season);
+
+        let mut stats = self.stats(&player).await?.unwrap_or_default();
+        stats.total_races = stats.total_races.saturating_sub(1);
+        stats.best_wpm = tally.best_wpm();
+        if let Some(outcome) = effect.outcome.as_ref().filter(|outcome| outcome.won) {
+            stats.wins = stats.wins.saturating_sub(1);
+            // Every race in the current streak came after this one, so the best
+            // streak is either the one before it or what is left of the current one.
+            if outcome.race > tally.last_loss {
+                stats.win_streak = stats.win_streak.saturating_sub(1);
+                stats.best_win_streak = outcome.best_win_streak_before.max(stats.win_streak);
+            }
+        }
+        self.put_stats(&player, stats)?;
+
+        if effect.season == *self.season.get() {
+            if let Some(mut season_stats) = self.season_stats.get(&player).await? {
+                season_stats.total_races = season_stats.total_races.saturating_sub(1);
+                season_stats.best_wpm = tally.season_best_wpm(effect.season);
+                if let Some(outcome) = &effect.outcome {
+                    if outcome.won {
+                        season_stats.wins = season_stats.wins.saturating_sub(1);
+                    }
+                    season_stats.apply_rating_delta(-outcome.rating_delta);
+                }
+                self.season_stats.insert(&player, season_stats)?;
+            }
+        }
+        self.result_tallies.insert(&player, tally)?;
+
+        if effect.reward > Amount::ZERO {
+            let debt = self.reward_debt(&player).await?.saturating_add(effect.reward);
+            self.reward_debts.insert(&player, debt)?;
+        }
         Ok(())
     }
+
+    /// Drops an invalidated race from the player's history index on this chain.
+    pub async fn void_race(&mut self, room_id: &str, player: &str) -> Result<(), StateError> {
+        let mut positions = self.race_index.get(player).await?.unwrap_or_default();
+        let mut found = None;
+        for (index, position) in positions.iter().enumerate().rev() {
+            let record = self.race_history.get(*position as usize).await?;
+            if record.is_some_and(|record| record.room_id == room_id) {
+                found = Some(index);
+                break;
+            }
+        }
+        if let Some(index) = found {
+            positions.remove(index);
+            self.race_index.insert(player, positions)?;
+        }
+        Ok(())
+    }
+
+    pub fn update_config(&mut self, admin: String, config: GameConfig, timestamp: u64) -> ModerationRecord {
+        self.config.set(config);
+        self.log_moderation(admin, ModerationKind::UpdateConfig, None, None, "Updated game config".to_string(), timestamp)
+    }
+
+    /// Deletes a room outright. Stats already credited from it are left as is.
+    pub async fn delete_room(
+        &mut self,
+        admin: String,
+        room_id: String,
+        reason: String,
+        timestamp: u64,
+    ) -> Result<ModerationRecord, StateError> {
+        if !self.has_room(&room_id).await? {
+            return Err(StateError::RoomNotFound);
+        }
+        self.remove_room(&room_id)?;
+        self.result_effects.remove(&room_id)?;
+        Ok(self.log_moderation(admin, ModerationKind::DeleteRoom, None, Some(room_id), reason, timestamp))
+    }
+
+    /// Records every milestone newly reached by `stats` and returns them.
+    async fn unlock_achievements(
+        &mut self,
+        player: &str,
+        stats: &PlayerStats,
+    ) -> Result<Vec<Achievement>, StateError> {
+        let player = player.to_string();
+        let mut unlocked = self.achievements.get(&player).await?.unwrap_or_default();
+        let new = achievements::newly_reached(stats, &unlocked);
+        if !new.is_empty() {
+            unlocked.extend(new.iter().copied());
+            self.achievements.insert(&player, unlocked)?;
+        }
+        Ok(new)
+    }
+
+    /// Mints a non-transferable badge for an achievement the player has unlocked.
+    pub async fn mint_badge(
+        &mut self,
+        player: String,
+        achievement: Achievement,
+        minted_at: u64,
+    ) -> Result<Badge, StateError> {
+        let unlocked = self.achievements.get(&player).await?.unwrap_or_default();
+        if !unlocked.contains(&achievement) {
+            return Err(StateError::AchievementLocked);
+        }
+        let mut owned = self.player_badges.get(&player).await?.unwrap_or_default();
+        for token_id in &owned {
+            if let Some(badge) = self.badges.get(token_id).await? {
+                if badge.achievement == achievement {
+                    return Err(StateError::BadgeAlreadyMinted);
+                }
+            }
+        }
+
+        let token_id = *self.next_badge_id.get();
+        self.next_badge_id.set(token_id + 1);
+        let badge = Badge {
+            token_id,
+            owner: player.clone(),
+            achievement,
+            name: achievement.name().to_string(),
+            minted_at,
+        };
+        self.badges.insert(&token_id, badge.clone())?;
+        owned.push(token_id);
+        self.player_badges.insert(&player, owned)?;
+        Ok(badge)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default, async_graphql::SimpleObject)]
@@ -122,6 +1284,90 @@ pub struct Room {
     pub players: Vec<PlayerResult>,
     pub participants: Vec<String>,
     pub is_finished: bool,
+    /// Player capacity. `0` means unlimited.
+    pub max_players: u32,
+    pub mode: RoomMode,
+    pub segments: Vec<String>,
+    pub teams: Vec<Team>,
+    pub player_chains: Vec<PlayerChain>,
+    /// Language and scoring rule. Without it, client-reported WPM is used as is.
+    pub passage: Option<PassageInfo>,
+    pub phase: RoomPhase,
+    /// Spectator market opened at the countdown, if any.
+    pub market: Option<RaceMarket>,
+}
+
+/// Where a room is in its pre-race flow. Rooms without a spectator market accept
+/// results in every phase; rooms with one only once racing.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum RoomPhase {
+    #[default]
+    Lobby,
+    Countdown,
+    Racing,
+}
+
+/// Outcome of `TypeArenaState::invalidate_result`.
+#[derive(Debug)]
+pub struct InvalidatedResult {
+    pub record: ModerationRecord,
+    /// Whether the room had finished, so the race was confirmed to the player's history.
+    pub confirmed: bool,
+    /// The chain the player raced from, when known.
+    pub chain_id: Option<ChainId>,
+}
+
+/// A racer's result as ranked when their room finishes.
+struct RankedRacer {
+    player: String,
+    wpm: u32,
+    accuracy: Option<u32>,
+}
+
+/// Outcome of `TypeArenaState::finish_room`.
+#[derive(Debug, Default)]
+pub struct FinishedRoom {
+    /// Relay standings, best first. Empty for solo rooms.
+    pub team_standings: Vec<TeamStanding>,
+    /// Achievements unlocked by the final stat updates.
+    pub unlocked: Vec<(String, Achievement)>,
+    /// History entries for every racer, with the chain each should be confirmed to
+    /// when known.
+    pub records: Vec<(Option<ChainId>, RaceRecord)>,
+    /// How to settle the room's spectator market, if it has one.
+    pub market: Option<MarketSettlement>,
+    /// Whether placements earn rewards: the room was racing and enough racers finished.
+    pub rewarded: bool,
+}
+
+impl Room {
+    /// WPM for typing `text` in `time_ms` under the room's scoring rule, or the
+    /// client-reported value for rooms without passage metadata.
+    pub fn scored_wpm(&self, text: &str, reported_wpm: u32, time_ms: u64) -> u32 {
+        match &self.passage {
+            Some(passage) => passage.scoring.wpm(text, time_ms),
+            None => reported_wpm,
+        }
+    }
+
+    /// The fastest finisher, with ties going to the higher WPM.
+    pub fn winner(&self) -> Option<&PlayerResult> {
+        self.ranked_results().into_iter().next()
+    }
+
+    /// Solo results from first to last place.
+    pub fn ranked_results(&self) -> Vec<&PlayerResult> {
+        let mut results: Vec<&PlayerResult> = self.players.iter().collect();
+        results.sort_by_key(|p| (p.finish_time_ms, std::cmp::Reverse(p.wpm)));
+        results
+    }
+
+    /// Position in `teams` of the relay team the player belongs to.
+    pub fn team_of(&self, player: &str) -> Option<usize> {
+        self.teams
+            .iter()
+            .position(|team| team.members.iter().any(|member| member == player))
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default, async_graphql::SimpleObject)]
@@ -129,6 +1375,8 @@ pub struct PlayerResult {
     pub address: String,
     pub wpm: u32,
     pub finish_time_ms: u64,
+    /// Accuracy in percent, when the client reported it.
+    pub accuracy: Option<u32>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Default, async_graphql::SimpleObject)]
@@ -146,4 +1394,7 @@ pub struct PlayerStats {
     pub wins: u32,
     pub total_races: u32,
     pub best_wpm: u32,
+    pub win_streak: u32,
+    pub best_win_streak: u32,
+    pub tournaments_won: u32,
 }
\ No newline at end of file
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Keystroke timings a client recorded while typing a passage, attached to a result.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::InputObject)]
+pub struct KeystrokeProof {
+    /// Milliseconds before each keystroke, measured from the previous one or from
+    /// the start of the race for the first.
+    pub intervals_ms: Vec<u32>,
+}
+
+impl KeystrokeProof {
+    /// Whether the proof holds at least one keystroke per grapheme of `text` and
+    /// its intervals add up to the reported `time_ms`.
+    pub fn supports(&self, text: &str, time_ms: u64) -> bool {
+        let total: u64 = self.intervals_ms.iter().map(|&interval| u64::from(interval)).sum();
+        self.intervals_ms.len() >= text.graphemes(true).count() && total == time_ms
+    }
+}
+
+/// A player's reported result for a passage or relay segment.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::InputObject)]
+#[graphql(input_name = "SubmissionInput")]
+pub struct Submission {
+    pub wpm: u32,
+    pub time_ms: u64,
+    /// Required when the game config asks for keystroke proofs.
+    pub proof: Option<KeystrokeProof>,
+}
+
+impl Submission {
+    /// A result without a keystroke proof.
+    pub fn new(wpm: u32, time_ms: u64) -> Self {
+        Submission { wpm, time_ms, proof: None }
+    }
+}
@@ -0,0 +1,396 @@
+//! Versioned storage records for rooms, tournaments and player stats.
+//!
+//! Records are stored wrapped in an enum with one variant per layout, so adding a
+//! field never breaks reading what deployed chains already hold. To change a
+//! layout, copy the current struct here as the next `V<n>` type, add a variant for
+//! the new layout, extend `into_latest` and bump `SCHEMA_VERSION`.
+//!
+//! Version 1 records were written without a version tag, before this scheme
+//! existed. They stay in the `legacy_*` maps until they are rewritten or migrated.
+//!
+//! Enums such as `RoomMode` are shared with the live types: appending a variant
+//! keeps old records readable, appending a struct field does not.
+
+use linera_sdk::linera_base_types::ChainId;
+use serde::{Deserialize, Serialize};
+
+use crate::betting::{RaceBet, RaceBetKind, RaceMarket};
+use crate::history::PlayerChain;
+use crate::passage::{PassageInfo, ScoringRule};
+use crate::relay::{RoomMode, SegmentResult, Team};
+use crate::state::{PlayerResult, PlayerStats, Room, RoomPhase, Tournament};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RoomV1 {
+    pub id: String,
+    pub host: String,
+    pub text: String,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub players: Vec<PlayerResultV1>,
+    pub participants: Vec<String>,
+    pub is_finished: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerResultV1 {
+    pub address: String,
+    pub wpm: u32,
+    pub finish_time_ms: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TournamentV1 {
+    pub id: String,
+    pub host: String,
+    pub max_players: u32,
+    pub current_round: u32,
+    pub participants: Vec<String>,
+    pub winner: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerStatsV1 {
+    pub wins: u32,
+    pub total_races: u32,
+    pub best_wpm: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RoomV2 {
+    pub id: String,
+    pub host: String,
+    pub text: String,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub players: Vec<PlayerResultV2>,
+    pub participants: Vec<String>,
+    pub is_finished: bool,
+    pub max_players: u32,
+    pub mode: RoomMode,
+    pub segments: Vec<String>,
+    pub teams: Vec<TeamV2>,
+    pub player_chains: Vec<PlayerChainV2>,
+    pub passage: Option<PassageInfoV2>,
+    pub phase: RoomPhase,
+    pub market: Option<RaceMarketV2>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerResultV2 {
+    pub address: String,
+    pub wpm: u32,
+    pub finish_time_ms: u64,
+    pub accuracy: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TeamV2 {
+    pub index: u32,
+    pub members: Vec<String>,
+    pub segment_results: Vec<SegmentResultV2>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SegmentResultV2 {
+    pub segment: u32,
+    pub player: String,
+    pub wpm: u32,
+    pub time_ms: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerChainV2 {
+    pub player: String,
+    pub chain_id: ChainId,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PassageInfoV2 {
+    pub language: String,
+    pub scoring: ScoringRule,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RaceMarketV2 {
+    pub market_id: u64,
+    pub bet: RaceBetV2,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RaceBetV2 {
+    pub kind: RaceBetKind,
+    pub player: String,
+    pub opponent: Option<String>,
+    pub wpm: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerStatsV2 {
+    pub wins: u32,
+    pub total_races: u32,
+    pub best_wpm: u32,
+    pub win_streak: u32,
+    pub best_win_streak: u32,
+    pub tournaments_won: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum VersionedRoom {
+    V1(RoomV1),
+    V2(RoomV2),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum VersionedTournament {
+    V1(TournamentV1),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum VersionedPlayerStats {
+    V1(PlayerStatsV1),
+    V2(PlayerStatsV2),
+}
+
+impl VersionedRoom {
+    pub fn is_latest(&self) -> bool {
+        matches!(self, VersionedRoom::V2(_))
+    }
+
+    pub fn into_latest(self) -> Room {
+        match self {
+            VersionedRoom::V1(room) => Room {
+                id: room.id,
+                host: room.host,
+                text: room.text,
+                start_time: room.start_time,
+                end_time: room.end_time,
+                players: room
+                    .players
+                    .into_iter()
+                    .map(|result| PlayerResult {
+                        address: result.address,
+                        wpm: result.wpm,
+                        finish_time_ms: result.finish_time_ms,
+                        accuracy: None,
+                    })
+                    .collect(),
+                participants: room.participants,
+                is_finished: room.is_finished,
+                max_players: 0,
+                mode: RoomMode::Solo,
+                segments: vec![],
+                teams: vec![],
+                player_chains: vec![],
+                passage: None,
+                phase: RoomPhase::Lobby,
+                market: None,
+            },
+            VersionedRoom::V2(room) => Room {
+                id: room.id,
+                host: room.host,
+                text: room.text,
+                start_time: room.start_time,
+                end_time: room.end_time,
+                players: room
+                    .players
+                    .into_iter()
+                    .map(|result| PlayerResult {
+                        address: result.address,
+                        wpm: result.wpm,
+                        finish_time_ms: result.finish_time_ms,
+                        accuracy: result.accuracy,
+                    })
+                    .collect(),
+                participants: room.participants,
+                is_finished: room.is_finished,
+                max_players: room.max_players,
+                mode: room.mode,
+                segments: room.segments,
+                teams: room
+                    .teams
+                    .into_iter()
+                    .map(|team| Team {
+                        index: team.index,
+                        members: team.members,
+                        segment_results: team
+                            .segment_results
+                            .into_iter()
+                            .map(|result| SegmentResult {
+                                segment: result.segment,
+                                player: result.player,
+                                wpm: result.wpm,
+                                time_ms: result.time_ms,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+                player_chains: room
+                    .player_chains
+                    .into_iter()
+                    .map(|entry| PlayerChain { player: entry.player, chain_id: entry.chain_id })
+                    .collect(),
+                passage: room.passage.map(|passage| PassageInfo {
+                    language: passage.language,
+                    scoring: passage.scoring,
+                }),
+                phase: room.phase,
+                market: room.market.map(|market| RaceMarket {
+                    market_id: market.market_id,
+                    bet: RaceBet {
+                        kind: market.bet.kind,
+                        player: market.bet.player,
+                        opponent: market.bet.opponent,
+                        wpm: market.bet.wpm,
+                    },
+                }),
+            },
+        }
+    }
+}
+
+impl From<Room> for VersionedRoom {
+    fn from(room: Room) -> Self {
+        VersionedRoom::V2(RoomV2 {
+            id: room.id,
+            host: room.host,
+            text: room.text,
+            start_time: room.start_time,
+            end_time: room.end_time,
+            players: room
+                .players
+                .into_iter()
+                .map(|result| PlayerResultV2 {
+                    address: result.address,
+                    wpm: result.wpm,
+                    finish_time_ms: result.finish_time_ms,
+                    accuracy: result.accuracy,
+                })
+                .collect(),
+            participants: room.participants,
+            is_finished: room.is_finished,
+            max_players: room.max_players,
+            mode: room.mode,
+            segments: room.segments,
+            teams: room
+                .teams
+                .into_iter()
+                .map(|team| TeamV2 {
+                    index: team.index,
+                    members: team.members,
+                    segment_results: team
+                        .segment_results
+                        .into_iter()
+                        .map(|result| SegmentResultV2 {
+                            segment: result.segment,
+                            player: result.player,
+                            wpm: result.wpm,
+                            time_ms: result.time_ms,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            player_chains: room
+                .player_chains
+                .into_iter()
+                .map(|entry| PlayerChainV2 { player: entry.player, chain_id: entry.chain_id })
+                .collect(),
+            passage: room.passage.map(|passage| PassageInfoV2 {
+                language: passage.language,
+                scoring: passage.scoring,
+            }),
+            phase: room.phase,
+            market: room.market.map(|market| RaceMarketV2 {
+                market_id: market.market_id,
+                bet: RaceBetV2 {
+                    kind: market.bet.kind,
+                    player: market.bet.player,
+                    opponent: market.bet.opponent,
+                    wpm: market.bet.wpm,
+                },
+            }),
+        })
+    }
+}
+
+impl VersionedTournament {
+    pub fn is_latest(&self) -> bool {
+        matches!(self, VersionedTournament::V1(_))
+    }
+
+    pub fn into_latest(self) -> Tournament {
+        match self {
+            VersionedTournament::V1(tournament) => Tournament {
+                id: tournament.id,
+                host: tournament.host,
+                max_players: tournament.max_players,
+                current_round: tournament.current_round,
+                participants: tournament.participants,
+                winner: tournament.winner,
+            },
+        }
+    }
+}
+
+impl From<Tournament> for VersionedTournament {
+    fn from(tournament: Tournament) -> Self {
+        VersionedTournament::V1(TournamentV1 {
+            id: tournament.id,
+            host: tournament.host,
+            max_players: tournament.max_players,
+            current_round: tournament.current_round,
+            participants: tournament.participants,
+            winner: tournament.winner,
+        })
+    }
+}
+
+impl VersionedPlayerStats {
+    pub fn is_latest(&self) -> bool {
+        matches!(self, VersionedPlayerStats::V2(_))
+    }
+
+    pub fn into_latest(self) -> PlayerStats {
+        match self {
+            VersionedPlayerStats::V1(stats) => PlayerStats {
+                wins: stats.wins,
+                total_races: stats.total_races,
+                best_wpm: stats.best_wpm,
+                ..PlayerStats::default()
+            },
+            VersionedPlayerStats::V2(stats) => PlayerStats {
+                wins: stats.wins,
+                total_races: stats.total_races,
+                best_wpm: stats.best_wpm,
+                win_streak: stats.win_streak,
+                best_win_streak: stats.best_win_streak,
+                tournaments_won: stats.tournaments_won,
+            },
+        }
+    }
+}
+
+impl From<PlayerStats> for VersionedPlayerStats {
+    fn from(stats: PlayerStats) -> Self {
+        VersionedPlayerStats::V2(PlayerStatsV2 {
+            wins: stats.wins,
+            total_races: stats.total_races,
+            best_wpm: stats.best_wpm,
+            win_streak: stats.win_streak,
+            best_win_streak: stats.best_win_streak,
+            tournaments_won: stats.tournaments_won,
+        })
+    }
+}
+
+/// What one `MigrateState` batch rewrote.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, async_graphql::SimpleObject)]
+pub struct MigrationReport {
+    pub rooms: u32,
+    pub tournaments: u32,
+    pub player_stats: u32,
+    /// Whether every record is now at `SCHEMA_VERSION`.
+    pub complete: bool,
+}
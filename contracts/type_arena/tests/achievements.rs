@@ -0,0 +1,55 @@
+//! Tests for unlocking achievements and minting their badges.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use type_arena::Achievement;
+
+/// Winning a tournament unlocks `TournamentChampion`, whose badge can then be minted once.
+#[tokio::test]
+async fn test_tournament_champion_badge() {
+    let mut state = empty_state().await;
+    assert!(state
+        .mint_badge("player".to_string(), Achievement::TournamentChampion, 1)
+        .await
+        .is_err());
+
+    let unlocked = state
+        .award_tournament("cup".to_string(), "player".to_string(), "admin".to_string())
+        .await
+        .expect("Failed to award tournament");
+    assert_eq!(unlocked, vec![Achievement::TournamentChampion]);
+
+    let badge = state
+        .mint_badge("player".to_string(), Achievement::TournamentChampion, 2)
+        .await
+        .expect("Failed to mint badge");
+    assert_eq!(badge.owner, "player");
+    assert_eq!(badge.achievement, Achievement::TournamentChampion);
+    assert_eq!(badge.name, "Tournament Champion");
+    let owned = state.player_badges.get("player").await.expect("Failed to read badges");
+    assert_eq!(owned, Some(vec![badge.token_id]));
+
+    assert!(state
+        .mint_badge("player".to_string(), Achievement::TournamentChampion, 3)
+        .await
+        .is_err());
+}
+
+/// A second tournament win counts towards the stats but does not unlock the badge again.
+#[tokio::test]
+async fn test_tournament_champion_unlocks_once() {
+    let mut state = empty_state().await;
+    for tournament_id in ["spring", "summer"] {
+        state
+            .award_tournament(tournament_id.to_string(), "player".to_string(), "admin".to_string())
+            .await
+            .expect("Failed to award tournament");
+    }
+    let stats = state.stats("player").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!(stats.tournaments_won, 2);
+    let unlocked = state.achievements.get("player").await.expect("Failed to read achievements");
+    assert_eq!(unlocked, Some(vec![Achievement::TournamentChampion]));
+}
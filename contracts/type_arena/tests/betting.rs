@@ -0,0 +1,119 @@
+//! Tests for spectator markets on a room's pre-race flow.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use type_arena::{
+    betting::MarketSettlement,
+    state::{PlayerResult, StateError},
+    RaceBet, RaceBetKind, RaceMarket, Submission, TypeArenaState,
+};
+
+fn bet(kind: RaceBetKind, player: &str) -> RaceBet {
+    RaceBet { kind, player: player.to_string(), opponent: None, wpm: None }
+}
+
+/// Creates a room with alice and bob in it, counts down with a winner bet on
+/// alice and attaches market 7, as the contract does.
+async fn room_with_market(state: &mut TypeArenaState) {
+    common::open_room(state, "room", &["alice", "bob"]).await;
+    let bet = bet(RaceBetKind::Winner, "alice");
+    state.start_countdown("room".to_string(), "host", Some(&bet)).await.expect("Failed to start countdown");
+    state
+        .attach_market("room".to_string(), RaceMarket { market_id: 7, bet })
+        .await
+        .expect("Failed to attach market");
+}
+
+async fn submit(state: &mut TypeArenaState, player: &str, time_ms: u64) -> Result<(), StateError> {
+    state.submit_result("room".to_string(), player.to_string(), Submission::new(80, time_ms), None, 0).await.map(|_| ())
+}
+
+/// Bets only come true for players with a result.
+#[test]
+fn test_bet_outcome() {
+    let result = |address: &str, wpm| PlayerResult { address: address.to_string(), wpm, finish_time_ms: 0, accuracy: None };
+    let (alice, bob) = (result("alice", 90), result("bob", 70));
+    let ranked = [&alice, &bob];
+
+    assert!(bet(RaceBetKind::Winner, "alice").outcome(&ranked));
+    assert!(!bet(RaceBetKind::Winner, "bob").outcome(&ranked));
+    let head_to_head = |player: &str, opponent: &str| RaceBet {
+        opponent: Some(opponent.to_string()),
+        ..bet(RaceBetKind::HeadToHead, player)
+    };
+    assert!(head_to_head("bob", "carol").outcome(&ranked));
+    assert!(!head_to_head("carol", "bob").outcome(&ranked));
+    let over = |wpm| RaceBet { wpm: Some(wpm), ..bet(RaceBetKind::OverUnder, "alice") };
+    assert!(over(85).outcome(&ranked));
+    assert!(!over(90).outcome(&ranked));
+}
+
+/// Only solo rooms take bets, and only well-formed ones.
+#[tokio::test]
+async fn test_countdown_validates_bet() {
+    let mut state = empty_state().await;
+    state
+        .create_room("room".to_string(), "host".to_string(), "a b c".to_string(), None, 0)
+        .await
+        .expect("Failed to create room");
+    let invalid = bet(RaceBetKind::HeadToHead, "alice");
+    assert!(matches!(
+        state.start_countdown("room".to_string(), "host", Some(&invalid)).await,
+        Err(StateError::InvalidBet)
+    ));
+    let valid = bet(RaceBetKind::Winner, "alice");
+    assert!(matches!(
+        state.start_countdown("room".to_string(), "alice", Some(&valid)).await,
+        Err(StateError::NotHost)
+    ));
+    state.start_countdown("room".to_string(), "host", Some(&valid)).await.expect("Failed to start countdown");
+}
+
+/// With a market attached, results are rejected until betting locks at the start.
+#[tokio::test]
+async fn test_results_wait_for_race() {
+    let mut state = empty_state().await;
+    room_with_market(&mut state).await;
+    assert!(matches!(submit(&mut state, "alice", 1_000).await, Err(StateError::WrongPhase)));
+
+    let market_id = state.start_race("room".to_string(), "host", 0).await.expect("Failed to start race");
+    assert_eq!(market_id, Some(7));
+    submit(&mut state, "alice", 1_000).await.expect("Failed to submit result");
+}
+
+/// A race that ran resolves its market with the bet's outcome.
+#[tokio::test]
+async fn test_finish_resolves_market() {
+    let mut state = empty_state().await;
+    room_with_market(&mut state).await;
+    state.start_race("room".to_string(), "host", 0).await.expect("Failed to start race");
+    submit(&mut state, "alice", 1_000).await.expect("Failed to submit result");
+    submit(&mut state, "bob", 2_000).await.expect("Failed to submit result");
+
+    let finished = state.finish_room("room".to_string(), "host", false, 0).await.expect("Failed to finish room");
+    assert_eq!(finished.market, Some(MarketSettlement::Resolve { market_id: 7, came_true: true }));
+}
+
+/// Finishing a room whose race never started cancels its still open market.
+#[tokio::test]
+async fn test_finish_before_race_cancels_market() {
+    let mut state = empty_state().await;
+    room_with_market(&mut state).await;
+    let finished = state.finish_room("room".to_string(), "host", false, 0).await.expect("Failed to finish room");
+    assert_eq!(finished.market, Some(MarketSettlement::Cancel { market_id: 7 }));
+}
+
+/// Only the host or an admin can finish a room.
+#[tokio::test]
+async fn test_only_host_or_admin_finishes() {
+    let mut state = empty_state().await;
+    room_with_market(&mut state).await;
+    assert!(matches!(
+        state.finish_room("room".to_string(), "alice", false, 0).await,
+        Err(StateError::NotHost)
+    ));
+    state.finish_room("room".to_string(), "admin", true, 0).await.expect("Failed to finish room");
+}
@@ -0,0 +1,47 @@
+//! Fixtures shared by the state-level tests.
+
+// Each test file compiles its own copy and uses only some of the fixtures.
+#![allow(dead_code)]
+
+use linera_sdk::views::{KeyValueStore, View, ViewStorageContext};
+use type_arena::{state::FinishedRoom, Submission, TypeArenaState};
+
+/// Loads an empty state backed by an in-memory key-value store.
+pub async fn empty_state() -> TypeArenaState {
+    let context = ViewStorageContext::new_unchecked(KeyValueStore::mock().to_mut(), Vec::new(), ());
+    TypeArenaState::load(context).await.expect("Failed to load state")
+}
+
+/// Creates a solo room on "a b c", hosted by "host", and has each of `players` join it.
+pub async fn open_room(state: &mut TypeArenaState, room_id: &str, players: &[&str]) {
+    state
+        .create_room(room_id.to_string(), "host".to_string(), "a b c".to_string(), None, 0)
+        .await
+        .expect("Failed to create room");
+    for player in players {
+        state.join_room(room_id.to_string(), player.to_string()).await.expect("Failed to join room");
+    }
+}
+
+/// Submits each `(player, wpm, time_ms)` result to the room.
+pub async fn submit_results(state: &mut TypeArenaState, room_id: &str, results: &[(&str, u32, u64)]) {
+    for (player, wpm, time_ms) in results {
+        state
+            .submit_result(room_id.to_string(), player.to_string(), Submission::new(*wpm, *time_ms), None, 0)
+            .await
+            .expect("Failed to submit result");
+    }
+}
+
+/// Finishes the room as its host.
+pub async fn finish_room(state: &mut TypeArenaState, room_id: &str) -> FinishedRoom {
+    state.finish_room(room_id.to_string(), "host", false, 0).await.expect("Failed to finish room")
+}
+
+/// Opens a room for the players in `results`, submits their results and finishes it.
+pub async fn race(state: &mut TypeArenaState, room_id: &str, results: &[(&str, u32, u64)]) -> FinishedRoom {
+    let players: Vec<&str> = results.iter().map(|(player, ..)| *player).collect();
+    open_room(state, room_id, &players).await;
+    submit_results(state, room_id, results).await;
+    finish_room(state, room_id).await
+}
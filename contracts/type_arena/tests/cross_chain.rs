@@ -0,0 +1,122 @@
+//! Integration tests for joining and racing in type_arena rooms from player chains.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::{
+    linera_base_types::AccountOwner,
+    test::{QueryOutcome, TestValidator},
+};
+use type_arena::{GameConfig, Operation, Submission, TypeArenaAbi, TypeArenaParameters};
+
+/// Players on their own chains join a room on the host chain and submit results
+/// through cross-chain messages, which only take effect once the host handles them.
+#[tokio::test]
+async fn test_cross_chain_join_and_submit() {
+    let (validator, app_id, host_chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), None)
+    .await;
+    let alice_chain = validator.new_chain().await;
+    let bob_chain = validator.new_chain().await;
+    let alice = AccountOwner::from(alice_chain.public_key()).to_string();
+    let bob = AccountOwner::from(bob_chain.public_key()).to_string();
+
+    host_chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-1".to_string(),
+                    text: "how vexingly quick daft zebras jump".to_string(),
+                    passage: None,
+                },
+            );
+        })
+        .await;
+
+    for chain in [&alice_chain, &bob_chain] {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    app_id,
+                    Operation::JoinRoom {
+                        room_id: "room-1".to_string(),
+                        host_chain_id: host_chain.id(),
+                    },
+                );
+            })
+            .await;
+    }
+
+    let QueryOutcome { response, .. } = host_chain
+        .graphql_query(app_id, "query { room(roomId: \"room-1\") { participants } }")
+        .await;
+    assert_eq!(response["room"]["participants"].as_array().map(Vec::len), Some(0));
+
+    host_chain.handle_received_messages().await;
+
+    let QueryOutcome { response, .. } = host_chain
+        .graphql_query(app_id, "query { room(roomId: \"room-1\") { participants } }")
+        .await;
+    let participants = response["room"]["participants"].as_array().expect("participants list");
+    assert!(participants.iter().any(|p| *p == alice.as_str()));
+    assert!(participants.iter().any(|p| *p == bob.as_str()));
+
+    for (chain, wpm, time_ms) in [(&alice_chain, 95u32, 3_500u64), (&bob_chain, 70, 4_800)] {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    app_id,
+                    Operation::SubmitResult {
+                        room_id: "room-1".to_string(),
+                        submission: Submission::new(wpm, time_ms),
+                        accuracy: Some(96),
+                        host_chain_id: host_chain.id(),
+                    },
+                );
+            })
+            .await;
+    }
+    host_chain.handle_received_messages().await;
+
+    host_chain
+        .add_block(|block| {
+            block.with_operation(app_id, Operation::FinishRoom { room_id: "room-1".to_string() });
+        })
+        .await;
+
+    let query = format!(
+        "query {{ \
+            alice: playerStats(key: \"{alice}\") {{ wins totalRaces bestWpm }} \
+            bob: playerStats(key: \"{bob}\") {{ wins totalRaces bestWpm }} \
+        }}"
+    );
+    let QueryOutcome { response, .. } = host_chain.graphql_query(app_id, query).await;
+    assert_eq!(response["alice"]["wins"], 1);
+    assert_eq!(response["alice"]["totalRaces"], 1);
+    assert_eq!(response["alice"]["bestWpm"], 95);
+    assert_eq!(response["bob"]["wins"], 0);
+    assert_eq!(response["bob"]["totalRaces"], 1);
+    assert_eq!(response["bob"]["bestWpm"], 70);
+
+    // Stats live on the host chain only.
+    let QueryOutcome { response, .. } = alice_chain
+        .graphql_query(app_id, format!("query {{ playerStats(key: \"{alice}\") {{ wins }} }}"))
+        .await;
+    assert!(response["playerStats"].is_null());
+
+    // The result is confirmed back to each player's chain history.
+    alice_chain.handle_received_messages().await;
+    let QueryOutcome { response, .. } = alice_chain
+        .graphql_query(app_id, "query { myRaces { player roomId placement wpm accuracy } }")
+        .await;
+    let races = response["myRaces"].as_array().expect("race list");
+    assert_eq!(races.len(), 1);
+    assert_eq!(races[0]["player"], alice.as_str());
+    assert_eq!(races[0]["roomId"], "room-1");
+    assert_eq!(races[0]["placement"], 1);
+    assert_eq!(races[0]["wpm"], 95);
+    assert_eq!(races[0]["accuracy"], 96);
+}
@@ -0,0 +1,101 @@
+//! Tests for exporting finished-room results.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{empty_state, finish_room, open_room, race};
+use type_arena::{
+    export::{self, RoomResultRow},
+    versioning::PlayerStatsV1,
+    Room, RoomMode, Submission,
+};
+
+fn ids(page: &(Vec<Room>, Option<usize>)) -> Vec<&str> {
+    page.0.iter().map(|room| room.id.as_str()).collect()
+}
+
+/// Pages only cover finished rooms, in the order they finished, and deleted
+/// rooms keep their place so later offsets stay valid.
+#[tokio::test]
+async fn test_finished_room_page() {
+    let mut state = empty_state().await;
+    for room_id in ["r0", "r1", "r2", "r3"] {
+        open_room(&mut state, room_id, &[]).await;
+    }
+    for room_id in ["r2", "r0", "r3"] {
+        finish_room(&mut state, room_id).await;
+    }
+
+    let page = state.finished_room_page(0, 2).await.expect("Failed to read page");
+    assert_eq!((ids(&page), page.1), (vec!["r2", "r0"], Some(2)));
+    let page = state.finished_room_page(2, 2).await.expect("Failed to read page");
+    assert_eq!((ids(&page), page.1), (vec!["r3"], None));
+    let page = state.finished_room_page(10, 2).await.expect("Failed to read page");
+    assert_eq!((ids(&page), page.1), (vec![], None));
+
+    state
+        .delete_room("admin".to_string(), "r0".to_string(), "spam".to_string(), 0)
+        .await
+        .expect("Failed to delete room");
+    let page = state.finished_room_page(0, 2).await.expect("Failed to read page");
+    assert_eq!((ids(&page), page.1), (vec!["r2"], Some(2)));
+}
+
+/// Solo rooms export one row per result, best place first; unfinished rooms none.
+#[tokio::test]
+async fn test_room_rows() {
+    let mut state = empty_state().await;
+    open_room(&mut state, "room", &["alice", "bob"]).await;
+    for (player, wpm, time_ms) in [("alice", 60, 2_000), ("bob", 90, 1_000)] {
+        state
+            .submit_result("room".to_string(), player.to_string(), Submission::new(wpm, time_ms), Some(97), 0)
+            .await
+            .expect("Failed to submit result");
+    }
+    let room = state.room("room").await.expect("Failed to read room").expect("room exists");
+    assert!(export::room_rows(&room).is_empty());
+
+    finish_room(&mut state, "room").await;
+    let room = state.room("room").await.expect("Failed to read room").expect("room exists");
+    let rows = export::room_rows(&room);
+    let row = |player: &str, placement, wpm, time_ms| RoomResultRow {
+        room_id: "room".to_string(),
+        host: "host".to_string(),
+        mode: RoomMode::Solo,
+        start_time: Some(0),
+        team: None,
+        player: player.to_string(),
+        placement,
+        wpm,
+        time_ms,
+        accuracy: Some(97),
+    };
+    assert_eq!(rows, vec![row("bob", 1, 90, 1_000), row("alice", 2, 60, 2_000)]);
+    assert_eq!(export::to_ndjson(&rows).lines().count(), 2);
+}
+
+/// Stats pages cover every player once, legacy records last, and stop at the page end.
+#[tokio::test]
+async fn test_players_with_stats_page() {
+    let mut state = empty_state().await;
+    race(&mut state, "room", &[("alice", 60, 2_000), ("bob", 90, 1_000)]).await;
+    let stats = PlayerStatsV1 { wins: 1, total_races: 1, best_wpm: 70 };
+    state.legacy_player_stats.insert("carol", stats).expect("Failed to insert legacy stats");
+
+    let (first, next) = state.players_with_stats_page(0, 2).await.expect("Failed to read page");
+    assert_eq!((first.len(), next), (2, Some(2)));
+    let (second, next) = state.players_with_stats_page(2, 2).await.expect("Failed to read page");
+    assert_eq!((second, next), (vec!["carol".to_string()], None));
+    let (all, next) = state.players_with_stats_page(0, 3).await.expect("Failed to read page");
+    assert_eq!((all.len(), next), (3, None));
+    assert!(first.iter().all(|player| all.contains(player)));
+}
+
+/// Requested page sizes are clamped to `1..=MAX_PAGE_SIZE`.
+#[test]
+fn test_page_size() {
+    assert_eq!(export::page_size(None), export::DEFAULT_PAGE_SIZE as usize);
+    assert_eq!(export::page_size(Some(0)), 1);
+    assert_eq!(export::page_size(Some(u32::MAX)), export::MAX_PAGE_SIZE as usize);
+}
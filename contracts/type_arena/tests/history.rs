@@ -0,0 +1,38 @@
+//! Tests for the per-chain race history and its per-player index.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use type_arena::RaceRecord;
+
+fn record(player: &str, room_id: &str) -> RaceRecord {
+    RaceRecord {
+        player: player.to_string(),
+        room_id: room_id.to_string(),
+        passage: "a b c".to_string(),
+        placement: 1,
+        wpm: 60,
+        accuracy: None,
+        timestamp: 0,
+    }
+}
+
+/// Each player's index points at exactly their own records, oldest first.
+#[tokio::test]
+async fn test_race_index() {
+    let mut state = empty_state().await;
+    for (player, room_id) in [("alice", "r0"), ("bob", "r0"), ("alice", "r1"), ("alice", "r2")] {
+        state.record_history(record(player, room_id)).await.expect("Failed to record race");
+    }
+
+    let positions = state.race_index.get("alice").await.expect("Failed to read index").unwrap_or_default();
+    assert_eq!(positions, vec![0, 2, 3]);
+    for (position, room_id) in positions.into_iter().zip(["r0", "r1", "r2"]) {
+        let stored = state.race_history.get(position as usize).await.expect("Failed to read history");
+        assert_eq!(stored, Some(record("alice", room_id)));
+    }
+    let positions = state.race_index.get("bob").await.expect("Failed to read index");
+    assert_eq!(positions, Some(vec![1]));
+}
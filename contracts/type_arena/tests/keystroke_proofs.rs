@@ -0,0 +1,85 @@
+//! Tests for the keystroke proofs results must carry when the game config requires them.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use type_arena::{
+    relay::RelaySetup, state::StateError, GameConfig, KeystrokeProof, Submission, TypeArenaState,
+};
+
+/// A submission whose proof has one keystroke per character of `text`, each
+/// `interval_ms` after the last.
+fn proven(text: &str, interval_ms: u32) -> Submission {
+    let intervals_ms = vec![interval_ms; text.chars().count()];
+    let time_ms = intervals_ms.iter().map(|&interval| u64::from(interval)).sum();
+    Submission { wpm: 60, time_ms, proof: Some(KeystrokeProof { intervals_ms }) }
+}
+
+async fn requiring_proofs() -> TypeArenaState {
+    let mut state = empty_state().await;
+    let config = GameConfig { require_keystroke_proofs: true, ..GameConfig::default() };
+    state.update_config("admin".to_string(), config, 0);
+    state
+}
+
+/// Without the flag a result needs no proof; with it, a missing or inconsistent proof is rejected.
+#[tokio::test]
+async fn test_solo_results_need_proofs() {
+    let text = "a b c";
+    let mut state = empty_state().await;
+    state.create_room("open".to_string(), "host".to_string(), text.to_string(), None, 0).await.expect("Failed to create room");
+    state
+        .submit_result("open".to_string(), "alice".to_string(), Submission::new(60, 1_000), None, 0)
+        .await
+        .expect("Proofs are optional by default");
+
+    let mut state = requiring_proofs().await;
+    state.create_room("room".to_string(), "host".to_string(), text.to_string(), None, 0).await.expect("Failed to create room");
+    let result = state.submit_result("room".to_string(), "alice".to_string(), Submission::new(60, 1_000), None, 0).await;
+    assert!(matches!(result, Err(StateError::KeystrokeProofRequired)));
+    let wrong_time = Submission { time_ms: 900, ..proven(text, 200) };
+    let result = state.submit_result("room".to_string(), "alice".to_string(), wrong_time, None, 0).await;
+    assert!(matches!(result, Err(StateError::InvalidKeystrokeProof)));
+    let missing_keys = Submission {
+        proof: Some(KeystrokeProof { intervals_ms: vec![400, 300, 300] }),
+        ..proven(text, 200)
+    };
+    let result = state.submit_result("room".to_string(), "alice".to_string(), missing_keys, None, 0).await;
+    assert!(matches!(result, Err(StateError::InvalidKeystrokeProof)));
+
+    state
+        .submit_result("room".to_string(), "alice".to_string(), proven(text, 200), None, 0)
+        .await
+        .expect("Failed to submit a proven result");
+    let room = state.room("room").await.expect("Failed to read room").expect("room exists");
+    assert_eq!(room.players[0].finish_time_ms, 1_000);
+}
+
+/// Relay segments are held to the same rule, against the text of their own segment.
+#[tokio::test]
+async fn test_relay_segments_need_proofs() {
+    let mut state = requiring_proofs().await;
+    state
+        .create_relay_room(
+            "relay".to_string(),
+            "host".to_string(),
+            "one two three four".to_string(),
+            None,
+            0,
+            RelaySetup { team_count: 2, team_size: 2 },
+        )
+        .await
+        .expect("Failed to create relay room");
+    state.join_team("relay".to_string(), 0, "alice".to_string()).await.expect("Failed to join team");
+
+    let result = state.submit_segment("relay".to_string(), "alice".to_string(), Submission::new(60, 1_000), 0).await;
+    assert!(matches!(result, Err(StateError::KeystrokeProofRequired)));
+    let room = state.room("relay").await.expect("Failed to read room").expect("room exists");
+    let segment = room.segments[0].clone();
+    state
+        .submit_segment("relay".to_string(), "alice".to_string(), proven(&segment, 150), 0)
+        .await
+        .expect("Failed to submit a proven segment");
+}
@@ -0,0 +1,120 @@
+//! Tests for reading and migrating records written before state versioning.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use type_arena::{
+    relay::RelaySetup,
+    versioning::{PlayerResultV1, PlayerStatsV1, RoomV1, VersionedRoom, SCHEMA_VERSION},
+    PassageInfo, ScoringRule, Submission, TypeArenaState,
+};
+
+async fn legacy_state() -> TypeArenaState {
+    let mut state = common::empty_state().await;
+    for index in 0..3 {
+        let room_id = format!("room-{index}");
+        let room = RoomV1 {
+            id: room_id.clone(),
+            host: "host".to_string(),
+            text: "old passage".to_string(),
+            start_time: Some(0),
+            end_time: None,
+            players: vec![PlayerResultV1 { address: "player".to_string(), wpm: 80 + index, finish_time_ms: 4_000 }],
+            participants: vec!["player".to_string()],
+            is_finished: true,
+        };
+        state.legacy_rooms.insert(&room_id, room).expect("Failed to insert legacy room");
+    }
+    let stats = PlayerStatsV1 { wins: 2, total_races: 3, best_wpm: 82 };
+    state.legacy_player_stats.insert("player", stats).expect("Failed to insert legacy stats");
+    state
+}
+
+/// Legacy records are readable through the accessors before any migration.
+#[tokio::test]
+async fn test_legacy_records_upgrade_on_read() {
+    let state = legacy_state().await;
+
+    let room = state.room("room-1").await.expect("Failed to read room").expect("room exists");
+    assert_eq!(room.players[0].wpm, 81);
+    assert_eq!(room.players[0].accuracy, None);
+    assert!(room.is_finished);
+
+    let stats = state.stats("player").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.wins, stats.total_races, stats.best_wpm), (2, 3, 82));
+    assert_eq!(stats.win_streak, 0);
+}
+
+/// Migration moves records out of the legacy maps in batches and bumps the
+/// schema version once nothing is left.
+#[tokio::test]
+async fn test_migration_in_batches() {
+    let mut state = legacy_state().await;
+
+    let report = state.migrate(2).await.expect("Failed to migrate");
+    assert_eq!((report.rooms, report.player_stats), (2, 0));
+    assert!(!report.complete);
+    assert_eq!(*state.schema_version.get(), 0);
+
+    let report = state.migrate(10).await.expect("Failed to migrate");
+    assert_eq!((report.rooms, report.player_stats), (1, 1));
+    assert!(report.complete);
+    assert_eq!(*state.schema_version.get(), SCHEMA_VERSION);
+
+    assert_eq!(state.legacy_rooms.count().await.expect("Failed to count"), 0);
+    assert_eq!(state.legacy_player_stats.count().await.expect("Failed to count"), 0);
+    assert_eq!(state.rooms.count().await.expect("Failed to count"), 3);
+    // Finished legacy rooms join the export order as they are migrated.
+    assert_eq!(state.finished_rooms.count(), 3);
+    let room = state.room("room-2").await.expect("Failed to read room").expect("room exists");
+    assert_eq!(room.players[0].wpm, 82);
+}
+
+/// A legacy room rewritten before migration, here by invalidating a result, joins the
+/// export order at once, and migration does not add it a second time.
+#[tokio::test]
+async fn test_rewritten_legacy_room_is_exported() {
+    let mut state = legacy_state().await;
+    state
+        .invalidate_result("admin".to_string(), "room-1".to_string(), "player".to_string(), "cheating".to_string(), 0)
+        .await
+        .expect("Failed to invalidate result");
+
+    let (rooms, next) = state.finished_room_page(0, 10).await.expect("Failed to read page");
+    assert_eq!(rooms.iter().map(|room| room.id.as_str()).collect::<Vec<_>>(), vec!["room-1"]);
+    assert_eq!(next, None);
+    assert!(rooms[0].players.is_empty());
+
+    state.migrate(10).await.expect("Failed to migrate");
+    assert_eq!(state.finished_rooms.count(), 3);
+}
+
+/// Current rooms survive a round trip through the frozen V2 layout.
+#[tokio::test]
+async fn test_room_round_trips_through_v2() {
+    let mut state = common::empty_state().await;
+    let passage = PassageInfo { language: "en".to_string(), scoring: ScoringRule::Code };
+    state
+        .create_relay_room(
+            "relay".to_string(),
+            "host".to_string(),
+            "one two three four".to_string(),
+            Some(passage),
+            0,
+            RelaySetup { team_count: 2, team_size: 2 },
+        )
+        .await
+        .expect("Failed to create relay room");
+    state.join_team("relay".to_string(), 0, "alice".to_string()).await.expect("Failed to join team");
+    state
+        .submit_segment("relay".to_string(), "alice".to_string(), Submission::new(90, 1_000), 0)
+        .await
+        .expect("Failed to submit segment");
+    let room = state.room("relay").await.expect("Failed to read room").expect("room exists");
+
+    let bytes = bcs::to_bytes(&VersionedRoom::from(room.clone())).expect("Failed to serialize room");
+    let stored: VersionedRoom = bcs::from_bytes(&bytes).expect("Failed to deserialize room");
+    assert!(stored.is_latest());
+    assert_eq!(stored.into_latest(), room);
+}
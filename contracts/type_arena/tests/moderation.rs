@@ -0,0 +1,198 @@
+//! Tests for invalidating results after they were credited.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{empty_state, race};
+use linera_sdk::linera_base_types::Amount;
+use type_arena::{relay::RelaySetup, seasons::INITIAL_RATING, RewardPayout, RewardReason, Submission, TypeArenaState};
+
+async fn invalidate(state: &mut TypeArenaState, room_id: &str, player: &str) {
+    state
+        .invalidate_result("admin".to_string(), room_id.to_string(), player.to_string(), "cheating".to_string(), 0)
+        .await
+        .expect("Failed to invalidate result");
+}
+
+/// An invalidated win comes off the all-time and season stats, including the rating.
+#[tokio::test]
+async fn test_invalidate_win() {
+    let mut state = empty_state().await;
+    race(&mut state, "r0", &[("alice", 80, 1_000), ("bob", 60, 2_000)]).await;
+    race(&mut state, "r1", &[("alice", 120, 1_000), ("bob", 60, 2_000)]).await;
+
+    let invalidated = state
+        .invalidate_result("admin".to_string(), "r1".to_string(), "alice".to_string(), "cheating".to_string(), 0)
+        .await
+        .expect("Failed to invalidate result");
+    assert!(invalidated.confirmed);
+
+    let room = state.room("r1").await.expect("Failed to read room").expect("room exists");
+    assert!(room.players.iter().all(|result| result.address != "alice"));
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.wins, stats.total_races, stats.best_wpm), (1, 1, 80));
+    assert_eq!((stats.win_streak, stats.best_win_streak), (1, 1));
+
+    let season = state.season_stats.get("alice").await.expect("Failed to read season").expect("season stats exist");
+    assert_eq!((season.wins, season.total_races, season.best_wpm), (1, 1, 80));
+    assert_eq!(season.rating, INITIAL_RATING + 8);
+
+    assert!(state
+        .invalidate_result("admin".to_string(), "r1".to_string(), "alice".to_string(), "again".to_string(), 0)
+        .await
+        .is_err());
+}
+
+/// A win in the current streak is taken out of it, and the best streak falls back
+/// to what it was before that race. Wins from earlier streaks leave streaks alone.
+#[tokio::test]
+async fn test_invalidate_restores_streak() {
+    let mut state = empty_state().await;
+    race(&mut state, "r0", &[("alice", 80, 1_000), ("bob", 60, 2_000)]).await;
+    race(&mut state, "r1", &[("alice", 60, 2_000), ("bob", 80, 1_000)]).await;
+    for room_id in ["r2", "r3"] {
+        race(&mut state, room_id, &[("alice", 80, 1_000), ("bob", 60, 2_000)]).await;
+    }
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.wins, stats.win_streak, stats.best_win_streak), (3, 2, 2));
+
+    invalidate(&mut state, "r3", "alice").await;
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.wins, stats.win_streak, stats.best_win_streak), (2, 1, 1));
+
+    invalidate(&mut state, "r0", "alice").await;
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.wins, stats.win_streak, stats.best_win_streak), (1, 1, 1));
+}
+
+/// Results from a closed season only come off the all-time stats.
+#[tokio::test]
+async fn test_invalidate_after_season_closed() {
+    let mut state = empty_state().await;
+    race(&mut state, "r0", &[("alice", 80, 1_000)]).await;
+    state.close_season(1, true).await.expect("Failed to close season");
+    race(&mut state, "r1", &[("alice", 70, 1_000)]).await;
+
+    invalidate(&mut state, "r0", "alice").await;
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.total_races, stats.best_wpm), (1, 70));
+    let season = state.season_stats.get("alice").await.expect("Failed to read season").expect("season stats exist");
+    assert_eq!((season.total_races, season.best_wpm), (1, 70));
+}
+
+/// Relay segments can be invalidated, and the rest of the team cannot race again.
+#[tokio::test]
+async fn test_invalidate_relay_segment() {
+    let mut state = empty_state().await;
+    state
+        .create_relay_room(
+            "relay".to_string(),
+            "host".to_string(),
+            "one two three four".to_string(),
+            None,
+            0,
+            RelaySetup { team_count: 2, team_size: 2 },
+        )
+        .await
+        .expect("Failed to create relay room");
+    for (team, player) in [(0, "alice"), (0, "bob"), (1, "carol"), (1, "dave")] {
+        state.join_team("relay".to_string(), team, player.to_string()).await.expect("Failed to join team");
+    }
+    for player in ["alice", "bob"] {
+        state
+            .submit_segment("relay".to_string(), player.to_string(), Submission::new(90, 1_000), 0)
+            .await
+            .expect("Failed to submit segment");
+    }
+
+    invalidate(&mut state, "relay", "alice").await;
+    let room = state.room("relay").await.expect("Failed to read room").expect("room exists");
+    assert_eq!(room.teams[0].segment_results.len(), 1);
+    let stats = state.stats("alice").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!((stats.total_races, stats.best_wpm), (0, 0));
+    assert!(state
+        .submit_segment("relay".to_string(), "bob".to_string(), Submission::new(90, 1_000), 0)
+        .await
+        .is_err());
+}
+
+/// Invalidated segments cannot be raced again out of order, even by their own players.
+#[tokio::test]
+async fn test_invalidated_segment_not_resubmitted() {
+    let mut state = empty_state().await;
+    state
+        .create_relay_room(
+            "relay".to_string(),
+            "host".to_string(),
+            "one two three four five six".to_string(),
+            None,
+            0,
+            RelaySetup { team_count: 2, team_size: 3 },
+        )
+        .await
+        .expect("Failed to create relay room");
+    for player in ["alice", "bob", "carol"] {
+        state.join_team("relay".to_string(), 0, player.to_string()).await.expect("Failed to join team");
+        state
+            .submit_segment("relay".to_string(), player.to_string(), Submission::new(90, 1_000), 0)
+            .await
+            .expect("Failed to submit segment");
+    }
+
+    invalidate(&mut state, "relay", "alice").await;
+    invalidate(&mut state, "relay", "bob").await;
+    for player in ["bob", "alice"] {
+        assert!(state
+            .submit_segment("relay".to_string(), player.to_string(), Submission::new(90, 1_000), 0)
+            .await
+            .is_err());
+    }
+    let room = state.room("relay").await.expect("Failed to read room").expect("room exists");
+    let segments: Vec<u32> = room.teams[0].segment_results.iter().map(|result| result.segment).collect();
+    assert_eq!(segments, vec![2]);
+}
+
+/// A placement reward paid for an invalidated result becomes a debt.
+#[tokio::test]
+async fn test_invalidate_reward_becomes_debt() {
+    let mut state = empty_state().await;
+    race(&mut state, "r0", &[("alice", 80, 1_000)]).await;
+    let payout = RewardPayout {
+        player: "alice".to_string(),
+        amount: Amount::from_tokens(3),
+        reason: RewardReason::RacePlacement,
+        reference: "r0".to_string(),
+        position: 1,
+        paid_at: 0,
+    };
+    state.record_reward(payout).await.expect("Failed to record reward");
+
+    invalidate(&mut state, "r0", "alice").await;
+    assert_eq!(state.reward_debt("alice").await.expect("Failed to read debt"), Amount::from_tokens(3));
+    state.settle_reward_debt("alice", Amount::from_tokens(2)).await.expect("Failed to settle debt");
+    assert_eq!(state.reward_debt("alice").await.expect("Failed to read debt"), Amount::from_tokens(1));
+}
+
+/// Voiding a race drops it from the player's history index only.
+#[tokio::test]
+async fn test_void_race() {
+    let mut state = empty_state().await;
+    for room_id in ["r0", "r1"] {
+        for player in ["alice", "bob"] {
+            let record = type_arena::RaceRecord {
+                player: player.to_string(),
+                room_id: room_id.to_string(),
+                passage: "a b c".to_string(),
+                placement: 1,
+                wpm: 80,
+                accuracy: None,
+                timestamp: 0,
+            };
+            state.record_history(record).await.expect("Failed to record race");
+        }
+    }
+    state.void_race("r0", "alice").await.expect("Failed to void race");
+    assert_eq!(state.race_index.get("alice").await.expect("Failed to read index"), Some(vec![2]));
+    assert_eq!(state.race_index.get("bob").await.expect("Failed to read index"), Some(vec![1, 3]));
+}
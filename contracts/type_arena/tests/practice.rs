@@ -0,0 +1,69 @@
+//! Tests for practice statistics and library passage selection.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use type_arena::{practice, KeyStat};
+
+fn stat(key: &str, attempts: u32, errors: u32, total_latency_ms: u64) -> KeyStat {
+    KeyStat { key: key.to_string(), attempts, errors, total_latency_ms }
+}
+
+/// Sessions add up per key and bigram.
+#[tokio::test]
+async fn test_record_practice() {
+    let mut state = empty_state().await;
+    state
+        .record_practice("alice".to_string(), vec![stat("a", 4, 1, 400)], vec![stat("th", 2, 0, 300)])
+        .await
+        .expect("Failed to record practice");
+    state
+        .record_practice("alice".to_string(), vec![stat("a", 6, 2, 600), stat("b", 5, 0, 500)], vec![])
+        .await
+        .expect("Failed to record practice");
+
+    let profile = state.practice.get("alice").await.expect("Failed to read profile").expect("profile exists");
+    assert_eq!(profile.sessions, 2);
+    assert_eq!(profile.keys, vec![stat("a", 10, 3, 1_000), stat("b", 5, 0, 500)]);
+    assert_eq!(profile.bigrams, vec![stat("th", 2, 0, 300)]);
+}
+
+/// Weakest entries rank by error rate, then latency, and need enough attempts.
+#[test]
+fn test_weakest() {
+    let stats = vec![
+        stat("a", 10, 1, 1_000),
+        stat("b", 10, 3, 1_000),
+        stat("c", 10, 1, 3_000),
+        stat("d", practice::MIN_ATTEMPTS - 1, 4, 1_000),
+    ];
+    let keys: Vec<String> = practice::weakest(&stats, 5).into_iter().map(|stat| stat.key).collect();
+    assert_eq!(keys, vec!["b", "c", "a"]);
+    assert_eq!(practice::weakest(&stats, 1)[0].key, "b");
+}
+
+/// The lobby picks the library passage that drills the weakest bigrams most.
+#[tokio::test]
+async fn test_pick_library_passage() {
+    let mut state = empty_state().await;
+    assert_eq!(state.pick_library_passage(&[]).await.expect("Failed to pick passage"), None);
+
+    let plain = state.add_library_passage("a plain passage".to_string()).expect("Failed to add passage");
+    let drill = state.add_library_passage("the thin thorn".to_string()).expect("Failed to add passage");
+    assert_eq!((plain, drill), (0, 1));
+
+    state
+        .record_practice("alice".to_string(), vec![], vec![stat("th", 10, 5, 2_000), stat("pa", 10, 1, 1_000)])
+        .await
+        .expect("Failed to record practice");
+    let focus = state.focus_bigrams("alice").await.expect("Failed to read focus");
+    assert_eq!(focus, vec!["th".to_string(), "pa".to_string()]);
+
+    let picked = state.pick_library_passage(&focus).await.expect("Failed to pick passage");
+    assert_eq!(picked.map(|passage| passage.id), Some(drill));
+    // Without a focus every passage scores zero and the oldest wins.
+    let picked = state.pick_library_passage(&[]).await.expect("Failed to pick passage");
+    assert_eq!(picked.map(|passage| passage.id), Some(plain));
+}
@@ -0,0 +1,119 @@
+//! Tests for reward schedules, the daily treasury budget and tournament awards.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use abi::ONE_DAY_MICROS;
+use common::empty_state;
+use linera_sdk::linera_base_types::{Amount, ChainId, CryptoHash};
+use type_arena::{Achievement, RewardPayout, RewardReason, RewardSchedule, TypeArenaState};
+
+fn payout(amount: Amount, paid_at: u64) -> RewardPayout {
+    RewardPayout {
+        player: "player".to_string(),
+        amount,
+        reason: RewardReason::RacePlacement,
+        reference: "room".to_string(),
+        position: 1,
+        paid_at,
+    }
+}
+
+/// Creates a room where each player submits a result, racing it first if `race` is set,
+/// and returns whether finishing it earns placement rewards.
+async fn finish(state: &mut TypeArenaState, room_id: &str, players: &[&str], race: bool) -> bool {
+    common::open_room(state, room_id, players).await;
+    if race {
+        state.start_countdown(room_id.to_string(), "host", None).await.expect("Failed to start countdown");
+        state.start_race(room_id.to_string(), "host", 0).await.expect("Failed to start race");
+    }
+    let results: Vec<_> = players.iter().map(|player| (*player, 80, 1_000)).collect();
+    common::submit_results(state, room_id, &results).await;
+    common::finish_room(state, room_id).await.rewarded
+}
+
+/// Placements only earn rewards in rooms that raced with enough finishers.
+#[tokio::test]
+async fn test_rewarded_rooms() {
+    let mut state = empty_state().await;
+    assert!(!finish(&mut state, "solo", &["alice"], true).await);
+    assert!(!finish(&mut state, "lobby", &["alice", "bob"], false).await);
+    assert!(finish(&mut state, "raced", &["alice", "bob"], true).await);
+}
+
+#[test]
+fn test_schedule_tiers() {
+    let schedule = RewardSchedule {
+        placements: vec![Amount::from_tokens(3), Amount::from_tokens(1)],
+        season_standings: vec![Amount::from_tokens(50)],
+        ..RewardSchedule::default()
+    };
+    assert_eq!(schedule.placement(1), Amount::from_tokens(3));
+    assert_eq!(schedule.placement(2), Amount::from_tokens(1));
+    assert_eq!(schedule.placement(3), Amount::ZERO);
+    assert_eq!(schedule.placement(0), Amount::ZERO);
+    assert_eq!(schedule.season_rank(1), Amount::from_tokens(50));
+    assert_eq!(RewardSchedule::default().placement(1), Amount::ZERO);
+}
+
+/// Payouts are capped by what is left of the day's budget, which resets the next day.
+#[tokio::test]
+async fn test_daily_budget() {
+    let mut state = empty_state().await;
+    let budget = Amount::from_tokens(10);
+    let now = 3 * ONE_DAY_MICROS + 1;
+
+    state.record_reward(payout(Amount::from_tokens(8), now)).await.expect("Failed to record reward");
+    let available = state.reward_budget.get().available(now, budget, Amount::from_tokens(5));
+    assert_eq!(available, Amount::from_tokens(2));
+
+    state.record_reward(payout(available, now)).await.expect("Failed to record reward");
+    assert_eq!(state.reward_budget.get().available(now, budget, Amount::from_tokens(5)), Amount::ZERO);
+
+    let tomorrow = now + ONE_DAY_MICROS;
+    assert_eq!(state.reward_budget.get().available(tomorrow, budget, Amount::from_tokens(5)), Amount::from_tokens(5));
+    state.record_reward(payout(Amount::from_tokens(5), tomorrow)).await.expect("Failed to record reward");
+    assert_eq!(state.reward_budget.get().paid, Amount::from_tokens(5));
+    assert_eq!(state.reward_ledger.count(), 3);
+    let positions = state.reward_index.get("player").await.expect("Failed to read index");
+    assert_eq!(positions, Some(vec![0, 1, 2]));
+}
+
+#[tokio::test]
+async fn test_award_tournament() {
+    let mut state = empty_state().await;
+
+    let unlocked = state
+        .award_tournament("cup".to_string(), "player".to_string(), "admin".to_string())
+        .await
+        .expect("Failed to award tournament");
+    assert_eq!(unlocked, vec![Achievement::TournamentChampion]);
+    let tournament = state.tournament("cup").await.expect("Failed to read tournament").expect("tournament exists");
+    assert_eq!(tournament.winner.as_deref(), Some("player"));
+    let stats = state.stats("player").await.expect("Failed to read stats").expect("stats exist");
+    assert_eq!(stats.tournaments_won, 1);
+
+    assert!(state
+        .award_tournament("cup".to_string(), "other".to_string(), "admin".to_string())
+        .await
+        .is_err());
+}
+
+/// Season and tournament rewards go to the chain the player last raced from.
+#[tokio::test]
+async fn test_player_chain() {
+    let mut state = empty_state().await;
+    assert_eq!(state.player_chain("player").await.expect("Failed to read chain"), None);
+
+    for (room_id, chain) in [("r0", "first"), ("r1", "second")] {
+        finish(&mut state, room_id, &["player"], false).await;
+        let chain_id = ChainId(CryptoHash::test_hash(chain));
+        state
+            .record_player_chain(room_id.to_string(), "player".to_string(), chain_id)
+            .await
+            .expect("Failed to record chain");
+    }
+    let chain_id = state.player_chain("player").await.expect("Failed to read chain");
+    assert_eq!(chain_id, Some(ChainId(CryptoHash::test_hash("second"))));
+}
@@ -0,0 +1,301 @@
+//! Integration tests for type_arena rooms on a single host chain.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::{
+    linera_base_types::{AccountOwner, ApplicationId, Event},
+    serde_json,
+    test::{ActiveChain, BlockBuilder, QueryOutcome, TestValidator},
+};
+use type_arena::{
+    GameConfig, Operation, PassageInfo, ScoringRule, Submission, TypeArenaAbi, TypeArenaEvent,
+    TypeArenaParameters,
+};
+
+/// Creates a room, joins it, submits results and finishes it on the host chain,
+/// checking room state, player stats and emitted events along the way.
+#[tokio::test]
+async fn test_room_lifecycle() {
+    let (_validator, app_id, chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), None)
+    .await;
+    let player = AccountOwner::from(chain.public_key()).to_string();
+
+    let certificate = chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-1".to_string(),
+                    text: "the quick brown fox".to_string(),
+                    passage: None,
+                },
+            );
+            block.with_operation(
+                app_id,
+                Operation::JoinRoom {
+                    room_id: "room-1".to_string(),
+                    host_chain_id: chain.id(),
+                },
+            );
+        })
+        .await;
+    assert_eq!(
+        events(&certificate.inner().block().body.events),
+        vec![
+            TypeArenaEvent::RoomCreated { room_id: "room-1".to_string() },
+            TypeArenaEvent::PlayerJoined { room_id: "room-1".to_string(), player: player.clone() },
+        ]
+    );
+
+    let response = query(&chain, app_id, "query { room(roomId: \"room-1\") { host participants isFinished } }").await;
+    assert_eq!(response["room"]["host"], player.as_str());
+    assert_eq!(response["room"]["participants"][0], player.as_str());
+    assert_eq!(response["room"]["isFinished"], false);
+
+    let certificate = chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::SubmitResult {
+                    room_id: "room-1".to_string(),
+                    submission: Submission::new(85, 4_000),
+                    accuracy: Some(97),
+                    host_chain_id: chain.id(),
+                },
+            );
+        })
+        .await;
+    assert_eq!(
+        events(&certificate.inner().block().body.events),
+        vec![TypeArenaEvent::ResultSubmitted {
+            room_id: "room-1".to_string(),
+            player: player.clone(),
+            wpm: 85,
+        }]
+    );
+
+    let certificate = chain
+        .add_block(|block| {
+            block.with_operation(app_id, Operation::FinishRoom { room_id: "room-1".to_string() });
+        })
+        .await;
+    let events = events(&certificate.inner().block().body.events);
+    assert_eq!(
+        events[0],
+        TypeArenaEvent::RoomFinished { room_id: "room-1".to_string(), team_standings: vec![] }
+    );
+    assert!(events.contains(&TypeArenaEvent::AchievementUnlocked {
+        player: player.clone(),
+        achievement: type_arena::Achievement::FirstWin,
+    }));
+
+    let response = query(&chain, app_id, "query { room(roomId: \"room-1\") { isFinished players { address wpm finishTimeMs } } }").await;
+    assert_eq!(response["room"]["isFinished"], true);
+    assert_eq!(response["room"]["players"][0]["address"], player.as_str());
+    assert_eq!(response["room"]["players"][0]["wpm"], 85);
+    assert_eq!(response["room"]["players"][0]["finishTimeMs"], 4_000);
+
+    let stats_query = format!("query {{ playerStats(key: \"{player}\") {{ wins totalRaces bestWpm }} }}");
+    let response = query(&chain, app_id, &stats_query).await;
+    assert_eq!(response["playerStats"]["wins"], 1);
+    assert_eq!(response["playerStats"]["totalRaces"], 1);
+    assert_eq!(response["playerStats"]["bestWpm"], 85);
+}
+
+/// A second result from the same player is ignored and does not count as another race.
+#[tokio::test]
+async fn test_duplicate_result_is_ignored() {
+    let (_validator, app_id, chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), None)
+    .await;
+    let player = AccountOwner::from(chain.public_key()).to_string();
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-1".to_string(),
+                    text: "jumps over the lazy dog".to_string(),
+                    passage: None,
+                },
+            );
+            for (wpm, time_ms) in [(60, 6_000), (110, 3_000)] {
+                block.with_operation(
+                    app_id,
+                    Operation::SubmitResult {
+                        room_id: "room-1".to_string(),
+                        submission: Submission::new(wpm, time_ms),
+                        accuracy: None,
+                        host_chain_id: chain.id(),
+                    },
+                );
+            }
+        })
+        .await;
+
+    let response = query(&chain, app_id, "query { room(roomId: \"room-1\") { players { wpm } } }").await;
+    let players = response["room"]["players"].as_array().expect("players list");
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0]["wpm"], 60);
+
+    let stats_query = format!("query {{ playerStats(key: \"{player}\") {{ totalRaces bestWpm }} }}");
+    let response = query(&chain, app_id, &stats_query).await;
+    assert_eq!(response["playerStats"]["totalRaces"], 1);
+    assert_eq!(response["playerStats"]["bestWpm"], 60);
+}
+
+/// Results are rejected once a room is finished, and a room cannot be finished twice.
+#[tokio::test]
+async fn test_finished_room_rejects_changes() {
+    let (_validator, app_id, chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), None)
+    .await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-1".to_string(),
+                    text: "pack my box".to_string(),
+                    passage: None,
+                },
+            );
+            block.with_operation(app_id, Operation::FinishRoom { room_id: "room-1".to_string() });
+        })
+        .await;
+
+    let result = chain
+        .try_add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::SubmitResult {
+                    room_id: "room-1".to_string(),
+                    submission: Submission::new(70, 5_000),
+                    accuracy: None,
+                    host_chain_id: chain.id(),
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+
+    let result = chain
+        .try_add_block(|block| {
+            block.with_operation(app_id, Operation::FinishRoom { room_id: "room-1".to_string() });
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+/// Creating a room with an id that is already taken fails.
+#[tokio::test]
+async fn test_duplicate_room_is_rejected() {
+    let (_validator, app_id, chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), None)
+    .await;
+
+    let create = |block: &mut BlockBuilder| {
+        block.with_operation(
+            app_id,
+            Operation::CreateRoom {
+                room_id: "room-1".to_string(),
+                text: "sphinx of black quartz".to_string(),
+                passage: None,
+            },
+        );
+    };
+    chain.add_block(create).await;
+    assert!(chain.try_add_block(create).await.is_err());
+}
+
+/// Rooms with passage metadata score results under their own rule instead of trusting
+/// the reported WPM, and only configured languages may be used.
+#[tokio::test]
+async fn test_passage_scoring_and_languages() {
+    let config = GameConfig {
+        allowed_languages: vec!["ja".to_string()],
+        ..GameConfig::default()
+    };
+    let (_validator, app_id, chain) = TestValidator::with_current_application::<
+        TypeArenaAbi,
+        TypeArenaParameters,
+        Option<GameConfig>,
+    >(TypeArenaParameters::default(), Some(config))
+    .await;
+
+    let result = chain
+        .try_add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-en".to_string(),
+                    text: "the quick brown fox".to_string(),
+                    passage: None,
+                },
+            );
+        })
+        .await;
+    assert!(result.is_err());
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom {
+                    room_id: "room-ja".to_string(),
+                    text: "今日は良い天気".to_string(),
+                    passage: Some(PassageInfo {
+                        language: "ja".to_string(),
+                        scoring: ScoringRule::Graphemes,
+                    }),
+                },
+            );
+            block.with_operation(
+                app_id,
+                Operation::SubmitResult {
+                    room_id: "room-ja".to_string(),
+                    submission: Submission::new(250, 6_000),
+                    accuracy: None,
+                    host_chain_id: chain.id(),
+                },
+            );
+        })
+        .await;
+
+    // Seven characters in six seconds.
+    let response = query(&chain, app_id, "query { room(roomId: \"room-ja\") { players { wpm } } }").await;
+    assert_eq!(response["room"]["players"][0]["wpm"], 70);
+}
+
+async fn query(
+    chain: &ActiveChain,
+    app_id: ApplicationId<TypeArenaAbi>,
+    query: &str,
+) -> serde_json::Value {
+    let QueryOutcome { response, .. } = chain.graphql_query(app_id, query).await;
+    response
+}
+
+/// Decodes every type_arena event emitted by a block.
+fn events(block_events: &[Vec<Event>]) -> Vec<TypeArenaEvent> {
+    block_events
+        .iter()
+        .flatten()
+        .map(|event| bcs::from_bytes(&event.value).expect("Invalid type_arena event"))
+        .collect()
+}
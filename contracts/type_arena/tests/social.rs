@@ -0,0 +1,60 @@
+//! Tests for friend lists and room invitations.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::empty_state;
+use linera_sdk::linera_base_types::{ChainId, CryptoHash};
+use type_arena::{state::StateError, Friend};
+
+fn chain(name: &str) -> ChainId {
+    ChainId(CryptoHash::test_hash(name))
+}
+
+fn friend(player: &str, chain_name: &str) -> Friend {
+    Friend { player: player.to_string(), chain_id: chain(chain_name) }
+}
+
+/// Adding a friend twice updates their chain instead of duplicating the entry.
+#[tokio::test]
+async fn test_add_and_remove_friend() {
+    let mut state = empty_state().await;
+    state.add_friend("alice".to_string(), friend("bob", "bob-1")).await.expect("Failed to add friend");
+    state.add_friend("alice".to_string(), friend("carol", "carol")).await.expect("Failed to add friend");
+    state.add_friend("alice".to_string(), friend("bob", "bob-2")).await.expect("Failed to add friend");
+
+    let friends = state.friends.get("alice").await.expect("Failed to read friends").unwrap_or_default();
+    assert_eq!(friends, vec![friend("bob", "bob-2"), friend("carol", "carol")]);
+    assert_eq!(state.friend("alice", "bob").await.expect("bob is a friend"), friend("bob", "bob-2"));
+    // Friendship is one-way.
+    assert!(matches!(state.friend("bob", "alice").await, Err(StateError::FriendNotFound)));
+
+    state.remove_friend("alice".to_string(), "bob".to_string()).await.expect("Failed to remove friend");
+    assert!(matches!(state.friend("alice", "bob").await, Err(StateError::FriendNotFound)));
+    assert!(matches!(
+        state.remove_friend("alice".to_string(), "bob".to_string()).await,
+        Err(StateError::FriendNotFound)
+    ));
+}
+
+/// Invitations get increasing ids and can only be taken once, by their invitee.
+#[tokio::test]
+async fn test_invites() {
+    let mut state = empty_state().await;
+    let first = state
+        .receive_invite("alice".to_string(), "bob".to_string(), "room".to_string(), chain("host"), 10)
+        .expect("Failed to store invite");
+    let second = state
+        .receive_invite("carol".to_string(), "bob".to_string(), "other".to_string(), chain("host"), 20)
+        .expect("Failed to store invite");
+    assert_eq!((first.id, second.id), (0, 1));
+    assert_eq!(first.room_id, "room");
+    assert_eq!(first.received_at, 10);
+
+    assert!(matches!(state.take_invite(first.id, "carol").await, Err(StateError::NotInvitee)));
+    let taken = state.take_invite(first.id, "bob").await.expect("Failed to take invite");
+    assert_eq!(taken, first);
+    assert!(matches!(state.take_invite(first.id, "bob").await, Err(StateError::InviteNotFound)));
+    assert_eq!(state.invites.get(&second.id).await.expect("Failed to read invites"), Some(second));
+}
@@ -0,0 +1,201 @@
+//! Model-based property tests for `TypeArenaState` transitions.
+//!
+//! Random sequences of room operations are applied both to the real state, backed by an
+//! in-memory key-value store, and to a plain reference model. Every step must succeed or
+//! fail in both, and the resulting rooms and stats must match.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use proptest::prelude::*;
+use type_arena::{Submission, TypeArenaState};
+
+const ROOMS: usize = 3;
+const PLAYERS: usize = 4;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Create { room: usize },
+    Join { room: usize, player: usize },
+    Submit { room: usize, player: usize, wpm: u32, time_ms: u64 },
+    Finish { room: usize },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..ROOMS).prop_map(|room| Op::Create { room }),
+        (0..ROOMS, 0..PLAYERS).prop_map(|(room, player)| Op::Join { room, player }),
+        (0..ROOMS, 0..PLAYERS, 0..250u32, 1..20_000u64)
+            .prop_map(|(room, player, wpm, time_ms)| Op::Submit { room, player, wpm, time_ms }),
+        (0..ROOMS).prop_map(|room| Op::Finish { room }),
+    ]
+}
+
+#[derive(Clone, Debug, Default)]
+struct ModelRoom {
+    participants: Vec<String>,
+    /// `(player, wpm, time_ms)` in submission order.
+    results: Vec<(String, u32, u64)>,
+    finished: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ModelStats {
+    wins: u32,
+    total_races: u32,
+    best_wpm: u32,
+}
+
+#[derive(Default)]
+struct Model {
+    rooms: BTreeMap<String, ModelRoom>,
+    stats: BTreeMap<String, ModelStats>,
+}
+
+impl Model {
+    /// Applies an operation, returning whether it should succeed.
+    fn apply(&mut self, op: &Op) -> bool {
+        match op {
+            Op::Create { room } => {
+                let id = room_id(*room);
+                if self.rooms.contains_key(&id) {
+                    return false;
+                }
+                self.rooms.insert(id, ModelRoom::default());
+                true
+            }
+            Op::Join { room, player } => {
+                let Some(room) = self.rooms.get_mut(&room_id(*room)) else {
+                    return false;
+                };
+                if room.finished {
+                    return false;
+                }
+                let player = player_id(*player);
+                if !room.participants.contains(&player) {
+                    room.participants.push(player);
+                }
+                true
+            }
+            Op::Submit { room, player, wpm, time_ms } => {
+                let Some(room) = self.rooms.get_mut(&room_id(*room)) else {
+                    return false;
+                };
+                if room.finished {
+                    return false;
+                }
+                let player = player_id(*player);
+                if room.results.iter().all(|(address, _, _)| *address != player) {
+                    room.results.push((player.clone(), *wpm, *time_ms));
+                    let stats = self.stats.entry(player).or_default();
+                    stats.total_races += 1;
+                    stats.best_wpm = stats.best_wpm.max(*wpm);
+                }
+                true
+            }
+            Op::Finish { room } => {
+                let Some(room) = self.rooms.get_mut(&room_id(*room)) else {
+                    return false;
+                };
+                if room.finished {
+                    return false;
+                }
+                room.finished = true;
+                let winner = room
+                    .results
+                    .iter()
+                    .min_by_key(|(_, wpm, time_ms)| (*time_ms, std::cmp::Reverse(*wpm)))
+                    .map(|(address, _, _)| address.clone());
+                if let Some(winner) = winner {
+                    self.stats.entry(winner).or_default().wins += 1;
+                }
+                true
+            }
+        }
+    }
+}
+
+fn room_id(index: usize) -> String {
+    format!("room-{index}")
+}
+
+fn player_id(index: usize) -> String {
+    format!("player-{index}")
+}
+
+async fn apply_to_state(state: &mut TypeArenaState, op: &Op) -> bool {
+    match op {
+        Op::Create { room } => state
+            .create_room(room_id(*room), "host".to_string(), "some passage text".to_string(), None, 0)
+            .await
+            .is_ok(),
+        Op::Join { room, player } => state.join_room(room_id(*room), player_id(*player)).await.is_ok(),
+        Op::Submit { room, player, wpm, time_ms } => state
+            .submit_result(room_id(*room), player_id(*player), Submission::new(*wpm, *time_ms), None, 0)
+            .await
+            .is_ok(),
+        Op::Finish { room } => state.finish_room(room_id(*room), "host", false, 0).await.is_ok(),
+    }
+}
+
+async fn check(ops: Vec<Op>) {
+    let mut state = common::empty_state().await;
+    let mut model = Model::default();
+
+    for op in &ops {
+        let expected = model.apply(op);
+        let actual = apply_to_state(&mut state, op).await;
+        assert_eq!(actual, expected, "outcome mismatch for {op:?}");
+    }
+
+    let mut total_results = 0;
+    for index in 0..ROOMS {
+        let id = room_id(index);
+        let room = state.room(&id).await.expect("Failed to read room");
+        let Some(model_room) = model.rooms.get(&id) else {
+            assert!(room.is_none());
+            continue;
+        };
+        let room = room.expect("room exists in the model");
+
+        let mut unique = room.participants.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), room.participants.len(), "player counted twice in {id}");
+        assert_eq!(room.participants, model_room.participants);
+        assert_eq!(room.is_finished, model_room.finished);
+
+        let results: Vec<(String, u32, u64)> = room
+            .players
+            .iter()
+            .map(|p| (p.address.clone(), p.wpm, p.finish_time_ms))
+            .collect();
+        assert_eq!(results, model_room.results);
+        total_results += results.len() as u32;
+    }
+
+    let mut total_races = 0;
+    for index in 0..PLAYERS {
+        let id = player_id(index);
+        let stats = state.stats(&id).await.expect("Failed to read stats");
+        let actual = stats
+            .map(|s| ModelStats { wins: s.wins, total_races: s.total_races, best_wpm: s.best_wpm })
+            .unwrap_or_default();
+        assert_eq!(actual, model.stats.get(&id).cloned().unwrap_or_default(), "stats mismatch for {id}");
+        total_races += actual.total_races;
+    }
+    assert_eq!(total_races, total_results, "total_races must equal the number of results");
+}
+
+proptest! {
+    #[test]
+    fn state_matches_model(ops in proptest::collection::vec(op_strategy(), 0..60)) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to build runtime");
+        runtime.block_on(check(ops));
+    }
+}
@@ -0,0 +1,124 @@
+//! Integration tests for placement rewards paid through the token application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
+    test::{ActiveChain, QueryOutcome, TestValidator},
+};
+use token::{BonusSchedule, TokenAbi, TokenOperation, TokenParameters};
+use type_arena::{GameConfig, Operation, RewardSchedule, Submission, TypeArenaAbi, TypeArenaParameters};
+
+/// Two players race from their own chains. Finishing the room pays their placement
+/// rewards from the treasury to those chains and records them in the ledger.
+#[tokio::test]
+async fn test_placement_rewards() {
+    let (validator, arena_module) =
+        TestValidator::with_current_module::<TypeArenaAbi, TypeArenaParameters, Option<GameConfig>>().await;
+    let mut host_chain = validator.new_chain().await;
+    let host = AccountOwner::from(host_chain.public_key());
+
+    let token_module = host_chain
+        .publish_bytecode_files_in::<TokenAbi, TokenParameters, ()>("../../frontend/token")
+        .await;
+    let token_parameters = TokenParameters {
+        name: "Test Token".to_string(),
+        ticker_symbol: "TEST".to_string(),
+        decimals: 2,
+        master_chain: host_chain.id(),
+        owner: host,
+        initial_supply: Amount::ZERO,
+        max_supply: Amount::from_tokens(1_000),
+        minters: vec![host],
+        bonus_schedule: BonusSchedule {
+            base: Amount::from_tokens(1),
+            max_multiplier: 7,
+            cooldown_micros: abi::ONE_DAY_MICROS,
+        },
+    };
+    let token_app = host_chain.create_application(token_module, token_parameters, (), vec![]).await;
+
+    let parameters = TypeArenaParameters {
+        token_app: Some(token_app),
+        rewards: RewardSchedule {
+            placements: vec![Amount::from_tokens(3), Amount::from_tokens(1)],
+            daily_budget: Amount::from_tokens(100),
+            ..RewardSchedule::default()
+        },
+        ..TypeArenaParameters::default()
+    };
+    let app_id = host_chain
+        .create_application(arena_module, parameters, None::<GameConfig>, vec![token_app.forget_abi()])
+        .await;
+    let treasury = AccountOwner::from(app_id.forget_abi());
+    host_chain
+        .add_block(|block| {
+            block.with_operation(token_app, TokenOperation::Mint { to: treasury, amount: Amount::from_tokens(10) });
+            block.with_operation(
+                app_id,
+                Operation::CreateRoom { room_id: "room-1".to_string(), text: "a b c".to_string(), passage: None },
+            );
+        })
+        .await;
+
+    let alice_chain = validator.new_chain().await;
+    let bob_chain = validator.new_chain().await;
+    for chain in [&alice_chain, &bob_chain] {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    app_id,
+                    Operation::JoinRoom { room_id: "room-1".to_string(), host_chain_id: host_chain.id() },
+                );
+            })
+            .await;
+    }
+    host_chain.handle_received_messages().await;
+    host_chain
+        .add_block(|block| {
+            block.with_operation(app_id, Operation::StartCountdown { room_id: "room-1".to_string(), bet: None });
+            block.with_operation(app_id, Operation::StartRace { room_id: "room-1".to_string() });
+        })
+        .await;
+
+    for (chain, wpm, time_ms) in [(&alice_chain, 95u32, 3_500u64), (&bob_chain, 70, 4_800)] {
+        chain
+            .add_block(|block| {
+                block.with_operation(
+                    app_id,
+                    Operation::SubmitResult {
+                        room_id: "room-1".to_string(),
+                        submission: Submission::new(wpm, time_ms),
+                        accuracy: None,
+                        host_chain_id: host_chain.id(),
+                    },
+                );
+            })
+            .await;
+    }
+    host_chain.handle_received_messages().await;
+    host_chain
+        .add_block(|block| {
+            block.with_operation(app_id, Operation::FinishRoom { room_id: "room-1".to_string() });
+        })
+        .await;
+
+    assert_eq!(balance(&host_chain, token_app, treasury).await, Amount::from_tokens(6));
+    let QueryOutcome { response, .. } =
+        host_chain.graphql_query(app_id, "query { rewardLedger { amount position } }").await;
+    let ledger = response["rewardLedger"].as_array().expect("ledger list");
+    assert_eq!(ledger.len(), 2);
+
+    for (chain, tokens) in [(&alice_chain, 3), (&bob_chain, 1)] {
+        chain.handle_received_messages().await;
+        let player = AccountOwner::from(chain.public_key());
+        assert_eq!(balance(chain, token_app, player).await, Amount::from_tokens(tokens));
+    }
+}
+
+/// Reads `owner`'s token balance on `chain`.
+async fn balance(chain: &ActiveChain, token_app: ApplicationId<TokenAbi>, owner: AccountOwner) -> Amount {
+    let QueryOutcome { response, .. } =
+        chain.graphql_query(token_app, format!("query {{ balance(owner: \"{owner}\") }}")).await;
+    response["balance"].as_str().expect("Amounts are strings").parse().expect("Invalid amount")
+}
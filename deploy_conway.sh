@@ -109,7 +109,7 @@ echo "✓ Bytecode ID: $BYTECODE_ID"
 echo ""
 echo "[6/7] Creating application instance..."
 
-APP_OUTPUT=$(linera create-application "$BYTECODE_ID" --json-argument "null" 2>&1)
+APP_OUTPUT=$(linera create-application "$BYTECODE_ID" --json-parameters '{"admins": []}' --json-argument "null" 2>&1)
 echo "$APP_OUTPUT"
 
 # Extract Application ID
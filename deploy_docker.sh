@@ -42,7 +42,7 @@ fi
 echo "Bytecode ID: $BYTECODE_ID"
 
 echo "Creating Application..."
-APP_OUT=$(linera create-application "$BYTECODE_ID" --json-argument "null")
+APP_OUT=$(linera create-application "$BYTECODE_ID" --json-parameters '{"admins": []}' --json-argument "null")
 echo "$APP_OUT"
 APP_ID=$(echo "$APP_OUT" | grep "Application ID" | awk '{print $3}')
 
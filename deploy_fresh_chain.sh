@@ -41,7 +41,7 @@ echo "[3/4] Publishing bytecode and creating application..."
 CONTRACT_WASM="/app/contracts/type_arena/target/wasm32-unknown-unknown/release/type_arena_contract.wasm"
 SERVICE_WASM="/app/contracts/type_arena/target/wasm32-unknown-unknown/release/type_arena_service.wasm"
 
-APP_OUTPUT=$(linera publish-and-create "$CONTRACT_WASM" "$SERVICE_WASM" --json-argument null 2>&1)
+APP_OUTPUT=$(linera publish-and-create "$CONTRACT_WASM" "$SERVICE_WASM" --json-parameters '{"admins": []}' --json-argument null 2>&1)
 echo "$APP_OUTPUT"
 
 # Extract Application ID
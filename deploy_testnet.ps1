@@ -52,7 +52,7 @@ Write-Host "Module ID: $BytecodeId"
 
 Write-Host "Creating Application on Testnet..."
 # Using "null" for instantiation argument ()
-$AppOutput = linera create-application $BytecodeId --json-argument "null" | Out-String
+$AppOutput = linera create-application $BytecodeId --json-parameters '{"admins": []}' --json-argument "null" | Out-String
 $AppId = $AppOutput | Select-String "Application ID: ([a-f0-9]+)" | ForEach-Object { $_.Matches.Groups[1].Value }
 
 Write-Host "--------------------------------------------------------"
@@ -50,7 +50,7 @@ fi
 echo "Module ID: $MODULE_ID"
 
 echo "Creating Application on Testnet..."
-APP_ID=$(linera create-application "$MODULE_ID" --json-argument "null" | grep "Application ID:" | awk '{print $3}')
+APP_ID=$(linera create-application "$MODULE_ID" --json-parameters '{"admins": []}' --json-argument "null" | grep "Application ID:" | awk '{print $3}')
 echo "--------------------------------------------------------"
 echo "DEPLOYMENT SUCCESSFUL!"
 echo "--------------------------------------------------------"
@@ -103,14 +103,17 @@ pub const MIN_BET_AMOUNT: u128 = 100_000;
 /// Market creation fee (1 token)
 pub const MARKET_CREATION_FEE: u128 = 1_000_000;
 
-/// Platform fee percentage (5%)
+/// Default platform fee percentage (5%), used when the market parameters don't set one
 pub const PLATFORM_FEE_PERCENT: u8 = 5;
 
 /// Minimum market duration (1 minute)
 pub const MIN_MARKET_DURATION_MICROS: u64 = 60_000_000;
 
 /// Maximum market duration (24 hours)
-pub const MAX_MARKET_DURATION_MICROS: u64 = 86_400_000_000;
+pub const MAX_MARKET_DURATION_MICROS: u64 = ONE_DAY_MICROS;
+
+/// One day in microseconds
+pub const ONE_DAY_MICROS: u64 = 86_400_000_000;
 
 // ============================================================================
 // UTILITY FUNCTIONS
@@ -124,38 +127,55 @@ impl Market {
 
     /// Check if market is ready for resolution
     pub fn can_resolve(&self, current_time: Timestamp) -> bool {
-        self.status == MarketStatus::Open && current_time >= self.closes_at
-    }
-
-    /// Calculate payout for a winning bet
-    pub fn calculate_payout(&self, bet_amount: Amount) -> Amount {
-        if self.total_pool == Amount::ZERO {
-            return Amount::ZERO;
+        match self.status {
+            MarketStatus::Open => current_time >= self.closes_at,
+            MarketStatus::Locked => true,
+            MarketStatus::Resolved | MarketStatus::Cancelled => false,
         }
+    }
 
-        let winning_pool = match self.outcome {
-            Some(Outcome::Up) => self.up_pool,
-            Some(Outcome::Down) => self.down_pool,
-            None => return Amount::ZERO,
+    /// Calculate payout for a winning bet, after the platform takes `fee_percent` of the pool
+    pub fn calculate_payout(&self, bet_amount: Amount, fee_percent: u8) -> Amount {
+        let winning_pool = match self.winning_pool() {
+            Some(pool) if pool > Amount::ZERO => pool,
+            _ => return Amount::ZERO,
         };
 
-        if winning_pool == Amount::ZERO {
-            return Amount::ZERO;
-        }
-
         // Convert Amount to u128 for math operations
-        let total_pool_u128: u128 = self.total_pool.into();
         let bet_amount_u128: u128 = bet_amount.into();
         let winning_pool_u128: u128 = winning_pool.into();
+        let total_after_fee: u128 = self.total_pool.saturating_sub(self.platform_fee(fee_percent)).into();
 
-        // Payout = (bet_amount / winning_pool) * total_pool * 0.95
-        let total_after_fee = total_pool_u128.saturating_mul(95).saturating_div(100);
+        // Payout = (bet_amount / winning_pool) * (total_pool - platform_fee)
         let payout = bet_amount_u128
             .saturating_mul(total_after_fee)
             .saturating_div(winning_pool_u128);
 
         // Convert back to Amount
-        Amount::from_tokens(payout)
+        Amount::from_attos(payout)
+    }
+
+    /// Platform's share of a resolved market's pool. If nobody backed the
+    /// winning side the bets are refunded, so nothing is kept.
+    pub fn platform_fee(&self, fee_percent: u8) -> Amount {
+        if !self.has_winners() {
+            return Amount::ZERO;
+        }
+        let total_pool_u128: u128 = self.total_pool.into();
+        Amount::from_attos(total_pool_u128.saturating_mul(fee_percent.into()) / 100)
+    }
+
+    /// Whether a resolved market has bets on its outcome to pay out
+    pub fn has_winners(&self) -> bool {
+        self.winning_pool().is_some_and(|pool| pool > Amount::ZERO)
+    }
+
+    fn winning_pool(&self) -> Option<Amount> {
+        match self.outcome {
+            Some(Outcome::Up) => Some(self.up_pool),
+            Some(Outcome::Down) => Some(self.down_pool),
+            None => None,
+        }
     }
 }
 
@@ -0,0 +1,40 @@
+//! Tests for splitting a resolved market's pool between winners and the platform.
+
+use abi::{Market, MarketStatus, MarketType, Outcome};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, Timestamp};
+
+/// A resolved market where ten tokens were bet on `Up` only.
+fn resolved(outcome: Outcome) -> Market {
+    Market {
+        id: 0,
+        creator: AccountOwner::CHAIN,
+        market_type: MarketType::BinaryEvent { question: "Will it go up?".to_string() },
+        duration_micros: 60_000_000,
+        created_at: Timestamp::from(0),
+        closes_at: Timestamp::from(60_000_000),
+        status: MarketStatus::Resolved,
+        total_pool: Amount::from_tokens(10),
+        up_pool: Amount::from_tokens(10),
+        down_pool: Amount::ZERO,
+        outcome: Some(outcome),
+        resolved_at: Some(Timestamp::from(60_000_000)),
+    }
+}
+
+/// Winners share the pool after the platform's percentage.
+#[test]
+fn test_fee_from_backed_outcome() {
+    let market = resolved(Outcome::Up);
+    assert!(market.has_winners());
+    assert_eq!(market.platform_fee(5), Amount::from_millis(500));
+    assert_eq!(market.calculate_payout(Amount::from_tokens(10), 5), Amount::from_millis(9_500));
+}
+
+/// If nobody backed the outcome, the platform keeps nothing so the bets can be refunded.
+#[test]
+fn test_no_fee_without_winners() {
+    let market = resolved(Outcome::Down);
+    assert!(!market.has_winners());
+    assert_eq!(market.platform_fee(5), Amount::ZERO);
+    assert_eq!(market.calculate_payout(Amount::from_tokens(10), 5), Amount::ZERO);
+}
@@ -30,7 +30,7 @@ export class LineraService {
     async submitScore(roomId: string, wpm: number, timeMs: number, hostChainId: string) {
         console.log(`[Linera] Submitting score for room ${roomId}: ${wpm} WPM on host chain ${hostChainId}`);
         const application = await this.getApplication(this.marketAppId);
-        const query = `mutation { submitResult(roomId: "${roomId}", wpm: ${wpm}, timeMs: ${timeMs}, hostChainId: "${hostChainId}") }`;
+        const query = `mutation { submitResult(roomId: "${roomId}", submission: { wpm: ${wpm}, timeMs: ${timeMs} }, hostChainId: "${hostChainId}") }`;
         await application.query(query);
     }
 
@@ -4,7 +4,7 @@ mod state;
 
 use self::state::MarketState;
 use abi::{
-    Bet, Market, MarketStatus, Outcome, Payout, MARKET_CREATION_FEE, MAX_MARKET_DURATION_MICROS,
+    Bet, Market, MarketStatus, Payout, MARKET_CREATION_FEE, MAX_MARKET_DURATION_MICROS,
     MIN_BET_AMOUNT, MIN_MARKET_DURATION_MICROS,
 };
 use linera_sdk::linera_base_types::{Amount, Timestamp};
@@ -14,7 +14,7 @@ use linera_sdk::{
     Contract, ContractRuntime,
 };
 use market::{MarketMessage, MarketOperation, MarketParameters, MarketResponse};
-use token::{TokenOperation, TokenResponse};
+use token::{TokenError, TokenOperation, TokenResponse};
 
 pub struct MarketContract {
     state: MarketState,
@@ -41,7 +41,10 @@ impl Contract for MarketContract {
     }
 
     async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
-        self.runtime.application_parameters();
+        let params = self.runtime.application_parameters();
+        if params.fee_percent > 100 {
+            panic!("Fee percentage cannot exceed 100");
+        }
 
         log::info!(
             "Market app instantiated on chain {:?}",
@@ -62,40 +65,24 @@ impl Contract for MarketContract {
 
                 let duration_micros = duration_minutes * 60_000_000;
 
-                if duration_micros < MIN_MARKET_DURATION_MICROS
-                    || duration_micros > MAX_MARKET_DURATION_MICROS
-                {
+                if !(MIN_MARKET_DURATION_MICROS..=MAX_MARKET_DURATION_MICROS).contains(&duration_micros) {
                     panic!("Invalid market duration");
                 }
 
-                let params = self.runtime.application_parameters();
-                let balance_response: TokenResponse = self
-                    .runtime
-                    .call_application(true, params.token_app, &TokenOperation::Balance { owner: creator.to_string() });
-
-                let balance = match balance_response {
-                    TokenResponse::Balance(amount) => amount,
-                    _ => panic!("Unexpected response"),
-                };
-
+                // Fails the whole transaction if the creator cannot pay the fee.
+                // The fee is held in escrow until the platform wallet withdraws it.
                 let creation_fee = Amount::from_attos(MARKET_CREATION_FEE);
-                if balance < creation_fee {
-                    panic!("Insufficient balance for market creation fee");
-                }
-
-                let new_balance = balance.saturating_sub(creation_fee);
-                self.runtime
-                    .call_application(
-                        true,
-                        params.token_app,
-                        &TokenOperation::UpdateBalance {
-                            owner: creator.to_string(),
-                            amount: new_balance,
-                        },
-                    );
+                self.call_token(
+                    TokenOperation::Escrow {
+                        owner: creator,
+                        amount: creation_fee,
+                    },
+                    "create a market",
+                );
 
                 let market_id = *self.state.next_market_id.get();
                 *self.state.next_market_id.get_mut() += 1;
+                self.accrue_fee(market_id, creation_fee).await;
 
                 let current_time = self.runtime.system_time();
                 let closes_at = Timestamp::from(current_time.micros().saturating_add(duration_micros));
@@ -120,6 +107,13 @@ impl Contract for MarketContract {
                     .insert(&market_id, market.clone())
                     .expect("Failed to create market");
 
+                if let Some(source) = self.runtime.authenticated_caller_id() {
+                    self.state
+                        .sources
+                        .insert(&market_id, source)
+                        .expect("Failed to record market source");
+                }
+
                 log::info!(
                     "Market {} created by {:?}, closes at {:?}",
                     market_id,
@@ -157,30 +151,8 @@ impl Contract for MarketContract {
                     panic!("Market is closed");
                 }
 
-                let params = self.runtime.application_parameters();
-                let balance_response: TokenResponse = self
-                    .runtime
-                    .call_application(true, params.token_app, &TokenOperation::Balance { owner: bettor.to_string() });
-
-                let balance = match balance_response {
-                    TokenResponse::Balance(amount) => amount,
-                    _ => panic!("Unexpected response"),
-                };
-
-                if balance < amount {
-                    panic!("Insufficient balance");
-                }
-
-                let new_balance = balance.saturating_sub(amount);
-                self.runtime
-                    .call_application(
-                        true,
-                        params.token_app,
-                        &TokenOperation::UpdateBalance {
-                            owner: bettor.to_string(),
-                            amount: new_balance,
-                        },
-                    );
+                // Stakes are held in the token app's escrow until payout or refund.
+                self.call_token(TokenOperation::Escrow { owner: bettor, amount }, "bet");
 
                 let bet = Bet {
                     bettor,
@@ -261,17 +233,26 @@ impl Contract for MarketContract {
                 market.outcome = Some(outcome);
                 market.resolved_at = Some(current_time);
 
+                let platform_fee = market.platform_fee(params.fee_percent);
+                self.accrue_fee(market_id, platform_fee).await;
+                let has_winners = market.has_winners();
+
                 self.state
                     .markets
                     .insert(&market_id, market)
                     .expect("Failed to resolve market");
 
+                // With no one to pay out, every bettor gets their stake back.
+                if !has_winners {
+                    self.refund_bets(market_id).await;
+                }
+
                 log::info!("Market {} resolved with outcome {:?}", market_id, outcome);
 
                 MarketResponse::Ok
             }
 
-            MarketOperation::CancelMarket { market_id } => {
+            MarketOperation::LockMarket { market_id } => {
                 let mut market = self
                     .state
                     .markets
@@ -280,62 +261,96 @@ impl Contract for MarketContract {
                     .expect("Failed to get market")
                     .expect("Market not found");
 
-                let signer = self
-                    .runtime
-                    .authenticated_signer()
-                    .expect("Cancel must be signed");
+                let source = self
+                    .state
+                    .sources
+                    .get(&market_id)
+                    .await
+                    .expect("Failed to get market source");
+                let caller = self.runtime.authenticated_caller_id();
+                let signer = self.runtime.authenticated_signer();
 
-                if signer != market.creator {
-                    panic!("Only market creator can cancel");
+                // A market opened by an application follows that application's lifecycle.
+                match source {
+                    Some(source) if caller != Some(source) => {
+                        panic!("Only the application that opened the market can lock it")
+                    }
+                    None if signer != Some(market.creator) => panic!("Only market creator can lock"),
+                    _ => {}
                 }
 
                 if market.status != MarketStatus::Open {
-                    panic!("Can only cancel open markets");
+                    panic!("Can only lock open markets");
                 }
 
-                market.status = MarketStatus::Cancelled;
+                market.status = MarketStatus::Locked;
 
                 self.state
                     .markets
                     .insert(&market_id, market)
-                    .expect("Failed to cancel market");
+                    .expect("Failed to lock market");
+
+                log::info!("Market {} locked", market_id);
 
-                let bets = self
+                MarketResponse::Ok
+            }
+
+            MarketOperation::CancelMarket { market_id } => {
+                let mut market = self
                     .state
-                    .bets
+                    .markets
                     .get(&market_id)
                     .await
-                    .expect("Failed to get bets")
-                    .unwrap_or_default();
+                    .expect("Failed to get market")
+                    .expect("Market not found");
 
-                let params = self.runtime.application_parameters();
-                for bet in bets {
-                    let balance_response: TokenResponse = self
-                        .runtime
-                        .call_application(true, params.token_app, &TokenOperation::Balance { owner: bet.bettor.to_string() });
-
-                    let balance = match balance_response {
-                        TokenResponse::Balance(amount) => amount,
-                        _ => panic!("Unexpected response"),
-                    };
-
-                    let new_balance = balance.saturating_add(bet.amount);
-                    self.runtime
-                        .call_application(
-                            true,
-                            params.token_app,
-                            &TokenOperation::UpdateBalance {
-                                owner: bet.bettor.to_string(),
-                                amount: new_balance,
-                            },
-                        );
+                let source = self
+                    .state
+                    .sources
+                    .get(&market_id)
+                    .await
+                    .expect("Failed to get market source");
+                let caller = self.runtime.authenticated_caller_id();
+                let signer = self.runtime.authenticated_signer();
+
+                match source {
+                    Some(source) if caller != Some(source) => {
+                        panic!("Only the application that opened the market can cancel it")
+                    }
+                    None if signer != Some(market.creator) => panic!("Only market creator can cancel"),
+                    _ => {}
+                }
+
+                // A locked market whose event never happens would otherwise hold its stakes forever.
+                if !matches!(market.status, MarketStatus::Open | MarketStatus::Locked) {
+                    panic!("Can only cancel open or locked markets");
                 }
 
+                market.status = MarketStatus::Cancelled;
+
+                self.state
+                    .markets
+                    .insert(&market_id, market)
+                    .expect("Failed to cancel market");
+
+                self.refund_bets(market_id).await;
+
                 log::info!("Market {} cancelled, all bets refunded", market_id);
 
                 MarketResponse::Ok
             }
 
+            MarketOperation::GetSource { market_id } => {
+                let source = self
+                    .state
+                    .sources
+                    .get(&market_id)
+                    .await
+                    .expect("Failed to get market source");
+
+                MarketResponse::Source(source)
+            }
+
             MarketOperation::ClaimWinnings { market_id } => {
                 let claimer = self
                     .runtime
@@ -380,10 +395,11 @@ impl Contract for MarketContract {
                     panic!("No bets placed");
                 }
 
+                let fee_percent = self.runtime.application_parameters().fee_percent;
                 let mut total_payout = Amount::ZERO;
                 for bet in &user_bets {
                     if bet.prediction == outcome.into() {
-                        let payout = market.calculate_payout(bet.amount);
+                        let payout = market.calculate_payout(bet.amount, fee_percent);
                         total_payout = total_payout.saturating_add(payout);
                     }
                 }
@@ -397,26 +413,13 @@ impl Contract for MarketContract {
                     .insert(&(market_id, claimer), true)
                     .expect("Failed to mark as claimed");
 
-                let params = self.runtime.application_parameters();
-                let balance_response: TokenResponse = self
-                    .runtime
-                    .call_application(true, params.token_app, &TokenOperation::Balance { owner: claimer.to_string() });
-
-                let balance = match balance_response {
-                    TokenResponse::Balance(amount) => amount,
-                    _ => panic!("Unexpected response"),
-                };
-
-                let new_balance = balance.saturating_add(total_payout);
-                self.runtime
-                    .call_application(
-                        true,
-                        params.token_app,
-                        &TokenOperation::UpdateBalance {
-                            owner: claimer.to_string(),
-                            amount: new_balance,
-                        },
-                    );
+                self.call_token(
+                    TokenOperation::Release {
+                        to: claimer,
+                        amount: total_payout,
+                    },
+                    "pay out winnings",
+                );
 
                 let payout = Payout {
                     market_id,
@@ -435,6 +438,55 @@ impl Contract for MarketContract {
 
                 MarketResponse::Payout(total_payout)
             }
+
+            MarketOperation::WithdrawFees => {
+                let params = self.runtime.application_parameters();
+                let signer = self
+                    .runtime
+                    .authenticated_signer()
+                    .expect("Withdrawal must be signed");
+
+                if signer != params.platform_wallet {
+                    panic!("Only the platform wallet can withdraw fees");
+                }
+
+                let mut market_ids = Vec::new();
+                let mut total_fees = Amount::ZERO;
+                self.state
+                    .fees
+                    .for_each_index_value(|market_id, fee| {
+                        market_ids.push(market_id);
+                        total_fees = total_fees.saturating_add(*fee);
+                        Ok(())
+                    })
+                    .await
+                    .expect("Failed to read fees");
+
+                if total_fees == Amount::ZERO {
+                    panic!("No fees to withdraw");
+                }
+
+                for market_id in market_ids {
+                    self.state
+                        .fees
+                        .remove(&market_id)
+                        .expect("Failed to clear fees");
+                }
+                let withdrawn = self.state.withdrawn_fees.get_mut();
+                *withdrawn = withdrawn.saturating_add(total_fees);
+
+                self.call_token(
+                    TokenOperation::Release {
+                        to: params.platform_wallet,
+                        amount: total_fees,
+                    },
+                    "withdraw fees",
+                );
+
+                log::info!("Withdrew {} in fees to {:?}", total_fees, params.platform_wallet);
+
+                MarketResponse::Payout(total_fees)
+            }
         }
     }
 
@@ -469,3 +521,57 @@ impl Contract for MarketContract {
         self.state.save().await.expect("Failed to save state");
     }
 }
+
+impl MarketContract {
+    /// Adds to the fees held for a market until the platform wallet withdraws them.
+    async fn accrue_fee(&mut self, market_id: u64, amount: Amount) {
+        if amount == Amount::ZERO {
+            return;
+        }
+        let accrued = self
+            .state
+            .fees
+            .get(&market_id)
+            .await
+            .expect("Failed to get fees")
+            .unwrap_or(Amount::ZERO);
+        self.state
+            .fees
+            .insert(&market_id, accrued.saturating_add(amount))
+            .expect("Failed to update fees");
+    }
+
+    /// Releases every bet on the market back to its bettor.
+    async fn refund_bets(&mut self, market_id: u64) {
+        let bets = self
+            .state
+            .bets
+            .get(&market_id)
+            .await
+            .expect("Failed to get bets")
+            .unwrap_or_default();
+
+        for bet in bets {
+            self.call_token(
+                TokenOperation::Release {
+                    to: bet.bettor,
+                    amount: bet.amount,
+                },
+                "refund",
+            );
+        }
+    }
+
+    /// Calls the token app, aborting with a readable message if it reports an error.
+    fn call_token(&mut self, operation: TokenOperation, action: &str) {
+        let params = self.runtime.application_parameters();
+        let response = self.runtime.call_application(true, params.token_app, &operation);
+        match response {
+            TokenResponse::Error(TokenError::InsufficientBalance) => {
+                panic!("Insufficient balance to {}", action)
+            }
+            TokenResponse::Error(error) => panic!("Failed to {}: {}", action, error),
+            _ => {}
+        }
+    }
+}
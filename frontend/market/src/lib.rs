@@ -1,6 +1,6 @@
-use abi::{Market, MarketType, Outcome, Prediction};
+use abi::{Market, MarketType, Outcome, Prediction, PLATFORM_FEE_PERCENT};
 use async_graphql::{Request, Response};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId, Timestamp};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId};
 use linera_sdk::{
     graphql::GraphQLMutationRoot,
     linera_base_types::{ContractAbi, ServiceAbi},
@@ -42,15 +42,29 @@ pub enum MarketOperation {
         outcome: Outcome,
     },
 
-    /// Cancel a market (admin only, refunds all bets)
+    /// Stop accepting bets before the market closes (source app only, or the creator
+    /// of a market no application opened)
+    LockMarket {
+        market_id: u64,
+    },
+
+    /// Cancel an open or locked market (source app only, or the creator of a market no
+    /// application opened; refunds all bets)
     CancelMarket {
         market_id: u64,
     },
 
+    /// Return the application that opened a market, if any
+    GetSource {
+        market_id: u64,
+    },
+
     /// Claim winnings from a resolved market
     ClaimWinnings {
         market_id: u64,
     },
+    /// Pay all accrued fees to the platform wallet (platform wallet only)
+    WithdrawFees,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -83,6 +97,7 @@ pub enum MarketResponse {
     MarketId(u64),
     Market(Market),
     Payout(Amount),
+    Source(Option<ApplicationId>),
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -95,4 +110,11 @@ pub struct MarketParameters {
 
     /// Oracle application ID for resolving markets
     pub oracle_app_id: ApplicationId,
+    /// Percentage of each resolved pool kept as a platform fee
+    #[serde(default = "default_fee_percent")]
+    pub fee_percent: u8,
+}
+
+fn default_fee_percent() -> u8 {
+    PLATFORM_FEE_PERCENT
 }
@@ -4,7 +4,7 @@ mod state;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
-    linera_base_types::{AccountOwner, WithServiceAbi},
+    linera_base_types::{Amount, WithServiceAbi},
     graphql::GraphQLMutationRoot,
     views::{MapView, View},
     Service, ServiceRuntime,
@@ -68,4 +68,12 @@ impl MarketService {
     async fn payouts(&self) -> &MapView<u64, Payout> {
         &self.state.payouts
     }
+
+    async fn fees(&self) -> &MapView<u64, Amount> {
+        &self.state.fees
+    }
+
+    async fn withdrawn_fees(&self) -> &Amount {
+        self.state.withdrawn_fees.get()
+    }
 }
@@ -1,5 +1,5 @@
 use abi::{Bet, Market, Payout};
-use linera_sdk::linera_base_types::AccountOwner;
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId};
 use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
 
 /// Market application state
@@ -23,4 +23,13 @@ pub struct MarketState {
 
     /// Payout records (key: payout_id, value: Payout)
     pub payouts: MapView<u64, Payout>,
+
+    /// Application that created each market, if any (market_id -> ApplicationId)
+    pub sources: MapView<u64, ApplicationId>,
+
+    /// Fees not yet withdrawn (market_id -> creation fee plus platform fee)
+    pub fees: MapView<u64, Amount>,
+
+    /// Fees paid out to the platform wallet so far
+    pub withdrawn_fees: RegisterView<Amount>,
 }
@@ -0,0 +1,128 @@
+//! Integration tests for stakes held in the token app's escrow, which the market may
+//! only touch once the token owner authorizes it.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use abi::{MarketType, Prediction, MARKET_CREATION_FEE};
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
+    serde_json,
+    test::{ActiveChain, QueryOutcome, TestValidator},
+};
+use market::{MarketAbi, MarketOperation, MarketParameters};
+use token::{BonusSchedule, TokenAbi, TokenOperation, TokenParameters};
+
+/// Creates the token and the market on one chain whose owner owns the token and holds
+/// ten tokens. The market is not authorized yet.
+async fn market_chain() -> (TestValidator, ApplicationId<TokenAbi>, ApplicationId<MarketAbi>, ActiveChain, AccountOwner) {
+    let (validator, market_module) = TestValidator::with_current_module::<MarketAbi, MarketParameters, ()>().await;
+    let mut chain = validator.new_chain().await;
+    let owner = AccountOwner::from(chain.public_key());
+
+    let token_module = chain.publish_bytecode_files_in::<TokenAbi, TokenParameters, ()>("../token").await;
+    let token_parameters = TokenParameters {
+        name: "Test Token".to_string(),
+        ticker_symbol: "TEST".to_string(),
+        decimals: 2,
+        master_chain: chain.id(),
+        owner,
+        initial_supply: Amount::ZERO,
+        max_supply: Amount::from_tokens(100),
+        minters: vec![owner],
+        bonus_schedule: BonusSchedule {
+            base: Amount::from_tokens(1),
+            max_multiplier: 7,
+            cooldown_micros: abi::ONE_DAY_MICROS,
+        },
+    };
+    let token_app = chain.create_application(token_module, token_parameters, (), vec![]).await;
+    chain
+        .add_block(|block| {
+            block.with_operation(token_app, TokenOperation::Mint { to: owner, amount: Amount::from_tokens(10) });
+        })
+        .await;
+
+    let market_parameters = MarketParameters {
+        token_app,
+        platform_wallet: owner,
+        oracle_app_id: token_app.forget_abi(),
+        fee_percent: 5,
+    };
+    let market_app = chain
+        .create_application(market_module, market_parameters, (), vec![token_app.forget_abi()])
+        .await;
+    (validator, token_app, market_app, chain, owner)
+}
+
+fn create_market() -> MarketOperation {
+    MarketOperation::CreateMarket {
+        market_type: MarketType::BinaryEvent { question: "Will it rain?".to_string() },
+        duration_minutes: 10,
+    }
+}
+
+/// The market can only escrow stakes while it is authorized, and cancelling a market
+/// releases every stake back to its bettor.
+#[tokio::test]
+async fn test_escrow_needs_authorization() {
+    let (_validator, token_app, market_app, chain, owner) = market_chain().await;
+    let fee = Amount::from_attos(MARKET_CREATION_FEE);
+    let stake = Amount::from_tokens(2);
+
+    let result = chain.try_add_block(|block| {
+        block.with_operation(market_app, create_market());
+    });
+    assert!(result.await.is_err());
+
+    chain
+        .add_block(|block| {
+            block.with_operation(token_app, TokenOperation::AuthorizeApp { app_id: market_app.forget_abi() });
+            block.with_operation(market_app, create_market());
+            block.with_operation(
+                market_app,
+                MarketOperation::PlaceBet { market_id: 0, prediction: Prediction::Up, amount: stake },
+            );
+        })
+        .await;
+    let (balance, escrowed) = holdings(&chain, token_app, owner, market_app).await;
+    assert_eq!(balance, Amount::from_tokens(10).saturating_sub(fee).saturating_sub(stake));
+    assert_eq!(escrowed, fee.saturating_add(stake));
+
+    chain
+        .add_block(|block| {
+            block.with_operation(market_app, MarketOperation::CancelMarket { market_id: 0 });
+        })
+        .await;
+    let (balance, escrowed) = holdings(&chain, token_app, owner, market_app).await;
+    assert_eq!(balance, Amount::from_tokens(10).saturating_sub(fee));
+    assert_eq!(escrowed, fee);
+
+    chain
+        .add_block(|block| {
+            block.with_operation(token_app, TokenOperation::RevokeApp { app_id: market_app.forget_abi() });
+        })
+        .await;
+    let result = chain.try_add_block(|block| {
+        block.with_operation(market_app, create_market());
+    });
+    assert!(result.await.is_err());
+}
+
+/// Returns `owner`'s token balance and the tokens the market holds in escrow.
+async fn holdings(
+    chain: &ActiveChain,
+    token_app: ApplicationId<TokenAbi>,
+    owner: AccountOwner,
+    market_app: ApplicationId<MarketAbi>,
+) -> (Amount, Amount) {
+    let query = format!(
+        "query {{ balance(owner: \"{owner}\") escrows {{ entry(key: \"{}\") {{ value }} }} }}",
+        market_app.forget_abi()
+    );
+    let QueryOutcome { response, .. } = chain.graphql_query(token_app, query).await;
+    (amount_of(&response["balance"]), amount_of(&response["escrows"]["entry"]["value"]))
+}
+
+fn amount_of(value: &serde_json::Value) -> Amount {
+    value.as_str().expect("Amounts are strings").parse().expect("Invalid amount")
+}
@@ -9,7 +9,7 @@ use linera_sdk::{
     views::{RootView, View},
     Contract, ContractRuntime,
 };
-use market::MarketOperation;
+use market::{MarketOperation, MarketResponse};
 use oracle::{OracleMessage, OracleOperation, OracleParameters, OracleResponse, PriceFeed};
 
 pub struct OracleContract {
@@ -131,6 +131,65 @@ impl Contract for OracleContract {
 
                 OracleResponse::Ok
             }
+
+            OracleOperation::AddTrustedSource { app_id } => {
+                let params = self.runtime.application_parameters();
+                let signer = self.runtime.authenticated_signer();
+
+                if signer != Some(params.owner) {
+                    panic!("Only oracle owner can add trusted sources");
+                }
+
+                let sources = self.state.trusted_sources.get_mut();
+                if !sources.contains(&app_id) {
+                    sources.push(app_id);
+                }
+
+                log::info!("Trusted outcome source added: {:?}", app_id);
+
+                OracleResponse::Ok
+            }
+
+            OracleOperation::ReportOutcome { market_id, outcome } => {
+                let caller = self
+                    .runtime
+                    .authenticated_caller_id()
+                    .expect("Outcomes must be reported by an application");
+
+                if !self.state.trusted_sources.get().contains(&caller) {
+                    panic!("Caller is not a trusted outcome source");
+                }
+
+                // A trusted source may only resolve the markets it opened itself.
+                let params = self.runtime.application_parameters();
+                let source = match self.runtime.call_application(
+                    true,
+                    params.market_app,
+                    &MarketOperation::GetSource { market_id },
+                ) {
+                    MarketResponse::Source(source) => source,
+                    response => panic!("Unexpected market response: {response:?}"),
+                };
+                if source != Some(caller) {
+                    panic!("Caller did not open this market");
+                }
+
+                self.runtime
+                    .call_application(
+                        true,
+                        params.market_app,
+                        &MarketOperation::ResolveMarket { market_id, outcome },
+                    );
+
+                log::info!(
+                    "Market {} resolved by {:?}: {:?}",
+                    market_id,
+                    caller,
+                    outcome
+                );
+
+                OracleResponse::Ok
+            }
         }
     }
 
@@ -35,6 +35,12 @@ pub enum OracleOperation {
 
     /// Manually resolve a market (admin only)
     ManualResolve { market_id: u64, outcome: Outcome },
+
+    /// Trust an application to report market outcomes (admin only)
+    AddTrustedSource { app_id: ApplicationId },
+
+    /// Resolve a market with an outcome reported by the trusted application that opened it
+    ReportOutcome { market_id: u64, outcome: Outcome },
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
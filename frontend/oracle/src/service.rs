@@ -5,6 +5,7 @@ mod state;
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
     abi::WithServiceAbi,
+    linera_base_types::ApplicationId,
     graphql::GraphQLMutationRoot,
     views::{MapView, View},
     Service, ServiceRuntime,
@@ -55,4 +56,8 @@ impl OracleService {
     async fn prices(&self) -> &MapView<String, PriceFeed> {
         &self.state.prices
     }
+
+    async fn trusted_sources(&self) -> &Vec<ApplicationId> {
+        self.state.trusted_sources.get()
+    }
 }
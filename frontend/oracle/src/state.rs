@@ -1,9 +1,13 @@
 use oracle::PriceFeed;
-use linera_sdk::views::{linera_views, MapView, RootView, ViewStorageContext};
+use linera_sdk::linera_base_types::ApplicationId;
+use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct OracleState {
     /// Latest price feeds per symbol
     pub prices: MapView<String, PriceFeed>,
+
+    /// Applications allowed to report market outcomes
+    pub trusted_sources: RegisterView<Vec<ApplicationId>>,
 }
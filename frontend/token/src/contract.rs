@@ -3,11 +3,14 @@
 mod state;
 
 use self::state::TokenState;
-use token::{DailyBonus, TokenMessage, TokenOperation, TokenParameters, TokenResponse};
-use linera_sdk::linera_base_types::{AccountOwner, Amount};
+use token::{
+    BonusClaim, TokenEvent, TokenMessage, TokenOperation, TokenError, TokenParameters, TokenRecord, TokenResponse,
+    TOKEN_STREAM,
+};
+use linera_sdk::linera_base_types::{Account, AccountOwner, Amount, ApplicationId};
 use linera_sdk::{
-    linera_base_types::WithContractAbi,
-    views::{RootView, View},
+    linera_base_types::{StreamName, WithContractAbi},
+    views::{MapView, RootView, View},
     Contract, ContractRuntime,
 };
 
@@ -26,7 +29,7 @@ impl Contract for TokenContract {
     type Message = TokenMessage;
     type Parameters = TokenParameters;
     type InstantiationArgument = ();
-    type EventValue = ();
+    type EventValue = TokenEvent;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = TokenState::load(runtime.root_view_storage_context())
@@ -37,9 +40,17 @@ impl Contract for TokenContract {
 
     async fn instantiate(&mut self, _argument: Self::InstantiationArgument) {
         let params = self.runtime.application_parameters();
+        // The master chain enforces the max supply, so the initial supply must start there.
+        assert_eq!(
+            self.runtime.chain_id(),
+            params.master_chain,
+            "The token must be created on its master chain"
+        );
+        assert!(params.initial_supply <= params.max_supply, "Initial supply exceeds the max supply");
 
         // Set initial total supply
         self.state.total_supply.set(params.initial_supply);
+        self.state.issued.set(params.initial_supply);
 
         log::info!(
             "Token app instantiated on chain {:?} with supply {}",
@@ -49,187 +60,587 @@ impl Contract for TokenContract {
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        let response = match self.execute(operation).await {
+            Ok(response) => response,
+            // Calling applications get the error back and decide how to react.
+            Err(error) if self.runtime.authenticated_caller_id().is_some() => {
+                log::info!("Token operation failed: {}", error);
+                TokenResponse::Error(error)
+            }
+            Err(error) => panic!("{}", error),
+        };
+        self.check_supply();
+        response
+    }
+
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            TokenMessage::Credit {
+                target,
+                amount,
+                source,
+            } => {
+                let is_bouncing = self
+                    .runtime
+                    .message_is_bouncing()
+                    .expect("Message delivery status must be available when executing a message");
+                let receiver = if is_bouncing { source } else { target };
+                // The tokens left the sending chain's supply and now join this one.
+                let total = self.state.total_supply.get_mut();
+                *total = total.saturating_add(amount);
+
+                log::info!(
+                    "Crediting {} to {:?}{}",
+                    amount,
+                    receiver,
+                    if is_bouncing { " (refund of bounced transfer)" } else { "" }
+                );
+                self.credit(receiver, amount).await;
+
+                let origin = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .expect("Message origin must be available when executing a message");
+                let sender = if is_bouncing { target } else { source };
+                self.record(TokenEvent::Received {
+                    from: Account {
+                        chain_id: origin,
+                        owner: sender,
+                    },
+                    to: receiver,
+                    amount,
+                })
+                .await;
+            }
+
+            TokenMessage::Withdraw {
+                owner,
+                amount,
+                target_account,
+            } => {
+                log::info!("Withdrawing {} from {:?} to {:?}", amount, owner, target_account);
+                self.check_permission(owner).expect("Failed to authorize withdrawal");
+                self.transfer(owner, target_account, amount)
+                    .await
+                    .expect("Failed to withdraw");
+            }
+
+            TokenMessage::TokensMinted { to, amount } => {
+                log::info!("Received tokens minted notification: {} to {:?}", amount, to);
+            }
+
+            TokenMessage::ClaimBonus { owner } => {
+                let origin = self
+                    .runtime
+                    .message_origin_chain_id()
+                    .expect("Message origin must be available when executing a message");
+                log::info!("Claiming daily bonus for {:?} on chain {:?}", owner, origin);
+                if self.runtime.authenticated_signer() != Some(owner) {
+                    panic!("{}", TokenError::NotPermitted(owner));
+                }
+                self.claim_bonus(owner, Account { chain_id: origin, owner })
+                    .await
+                    .expect("Failed to claim daily bonus");
+            }
+        }
+        self.check_supply();
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl TokenContract {
+    /// Runs an operation. Every check happens before any state is changed, so a failed
+    /// operation leaves no partial update behind.
+    async fn execute(&mut self, operation: TokenOperation) -> Result<TokenResponse, TokenError> {
         match operation {
             TokenOperation::Balance { owner } => {
-                let owner: AccountOwner = owner.parse().expect("Invalid owner");
                 log::info!("Getting balance for {:?}", owner);
+                Ok(TokenResponse::Balance(self.balance(&owner).await))
+            }
 
-                let balance = self
-                    .state
-                    .accounts
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get balance")
-                    .unwrap_or(Amount::ZERO);
-
-                TokenResponse::Balance(balance)
+            TokenOperation::TickerSymbol => {
+                Ok(TokenResponse::TickerSymbol(
+                    self.runtime.application_parameters().ticker_symbol,
+                ))
             }
 
-            TokenOperation::UpdateBalance { owner, amount } => {
-                let owner: AccountOwner = owner.parse().expect("Invalid owner");
-                log::info!("Updating balance for {:?} to {}", owner, amount);
+            TokenOperation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => {
+                self.check_permission(owner)?;
+                log::info!("{:?} approving {} for {:?}", owner, allowance, spender);
 
                 self.state
-                    .accounts
-                    .insert(&owner, amount)
-                    .expect("Failed to update balance");
+                    .allowances
+                    .insert(&(owner, spender), allowance)
+                    .expect("Failed to update allowance");
 
-                TokenResponse::Ok
+                Ok(TokenResponse::Ok)
             }
 
-            TokenOperation::Transfer { from, to, amount } => {
-                let from: AccountOwner = from.parse().expect("Invalid from address");
-                let to: AccountOwner = to.parse().expect("Invalid to address");
-                log::info!("Transferring {} from {:?} to {:?}", amount, from, to);
-
-                // Verify signer
-                let signer = self
-                    .runtime
-                    .authenticated_signer()
-                    .expect("Transfer must be signed");
+            TokenOperation::Transfer {
+                owner,
+                amount,
+                target_account,
+            } => {
+                self.check_permission(owner)?;
+                log::info!("Transferring {} from {:?} to {:?}", amount, owner, target_account);
 
-                if signer != from {
-                    panic!("Only account owner can transfer");
-                }
+                self.transfer(owner, target_account, amount).await?;
+                Ok(TokenResponse::Ok)
+            }
 
-                // Get balances
-                let from_balance = self
+            TokenOperation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => {
+                self.check_permission(spender)?;
+                log::info!(
+                    "{:?} transferring {} from {:?} to {:?}",
+                    spender,
+                    amount,
+                    owner,
+                    target_account
+                );
+
+                let allowance = self
                     .state
-                    .accounts
-                    .get(&from)
+                    .allowances
+                    .get(&(owner, spender))
                     .await
-                    .expect("Failed to get sender balance")
+                    .expect("Failed to get allowance")
                     .unwrap_or(Amount::ZERO);
+                if allowance < amount {
+                    return Err(TokenError::InsufficientAllowance);
+                }
+
+                // The transfer checks the balance before changing anything, so the allowance
+                // is only spent once it succeeded.
+                self.transfer(owner, target_account, amount).await?;
+                self.state
+                    .allowances
+                    .insert(&(owner, spender), allowance.saturating_sub(amount))
+                    .expect("Failed to update allowance");
+                Ok(TokenResponse::Ok)
+            }
 
-                if from_balance < amount {
-                    panic!("Insufficient balance");
+            TokenOperation::Claim {
+                source_account,
+                amount,
+                target_account,
+            } => {
+                self.check_permission(source_account.owner)?;
+                log::info!("Claiming {} from {:?} to {:?}", amount, source_account, target_account);
+
+                if source_account.chain_id == self.runtime.chain_id() {
+                    self.transfer(source_account.owner, target_account, amount).await?;
+                } else {
+                    self.runtime
+                        .prepare_message(TokenMessage::Withdraw {
+                            owner: source_account.owner,
+                            amount,
+                            target_account,
+                        })
+                        .with_authentication()
+                        .send_to(source_account.chain_id);
                 }
+                Ok(TokenResponse::Ok)
+            }
+
+            TokenOperation::Debit { owner, amount } => {
+                let caller = self.authorized_caller()?;
+                log::info!("{:?} debiting {} from {:?}", caller, amount, owner);
+
+                // Debited tokens are destroyed, like burned ones.
+                self.debit(owner, amount).await?;
+                self.destroy_supply(amount);
+                self.record(TokenEvent::Debited {
+                    app: caller,
+                    owner,
+                    amount,
+                })
+                .await;
+                Ok(TokenResponse::Ok)
+            }
 
-                let to_balance = self
+            TokenOperation::Credit { owner, amount } => {
+                let caller = self.authorized_caller()?;
+                log::info!("{:?} crediting {} to {:?}", caller, amount, owner);
+
+                // Credited tokens are new, so they count against the max supply like minted ones.
+                self.increase_supply(amount)?;
+                self.credit(owner, amount).await;
+                self.record(TokenEvent::Credited {
+                    app: caller,
+                    owner,
+                    amount,
+                })
+                .await;
+                Ok(TokenResponse::Ok)
+            }
+
+            TokenOperation::Escrow { owner, amount } => {
+                let caller = self.authorized_caller()?;
+                log::info!("{:?} escrowing {} from {:?}", caller, amount, owner);
+
+                self.debit(owner, amount).await?;
+                self.add_held(amount);
+                let escrowed = self
                     .state
-                    .accounts
-                    .get(&to)
+                    .escrows
+                    .get(&caller)
                     .await
-                    .expect("Failed to get receiver balance")
+                    .expect("Failed to get escrow")
                     .unwrap_or(Amount::ZERO);
-
-                // Update balances
                 self.state
-                    .accounts
-                    .insert(&from, from_balance.saturating_sub(amount))
-                    .expect("Failed to update sender balance");
+                    .escrows
+                    .insert(&caller, escrowed.saturating_add(amount))
+                    .expect("Failed to update escrow");
+                self.record(TokenEvent::Escrowed {
+                    app: caller,
+                    owner,
+                    amount,
+                })
+                .await;
+
+                Ok(TokenResponse::Ok)
+            }
 
+            TokenOperation::Release { to, amount } => {
+                let caller = self.authorized_caller()?;
+                log::info!("{:?} releasing {} to {:?}", caller, amount, to);
+
+                let escrowed = self
+                    .state
+                    .escrows
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get escrow")
+                    .unwrap_or(Amount::ZERO);
+                if escrowed < amount {
+                    return Err(TokenError::InsufficientEscrow);
+                }
                 self.state
-                    .accounts
-                    .insert(&to, to_balance.saturating_add(amount))
-                    .expect("Failed to update receiver balance");
+                    .escrows
+                    .insert(&caller, escrowed.saturating_sub(amount))
+                    .expect("Failed to update escrow");
+                self.remove_held(amount);
+                self.credit(to, amount).await;
+                self.record(TokenEvent::Released {
+                    app: caller,
+                    to,
+                    amount,
+                })
+                .await;
+
+                Ok(TokenResponse::Ok)
+            }
+
+            TokenOperation::AuthorizeApp { app_id } => {
+                self.check_owner()?;
+                let apps = self.state.authorized_apps.get_mut();
+                if !apps.contains(&app_id) {
+                    apps.push(app_id);
+                }
 
-                log::info!("Transfer completed");
-                TokenResponse::Ok
+                log::info!("Authorized application {:?}", app_id);
+                Ok(TokenResponse::Ok)
+            }
+
+            TokenOperation::RevokeApp { app_id } => {
+                self.check_owner()?;
+                self.state.authorized_apps.get_mut().retain(|app| *app != app_id);
+
+                log::info!("Revoked application {:?}", app_id);
+                Ok(TokenResponse::Ok)
             }
 
             TokenOperation::Mint { to, amount } => {
-                let to: AccountOwner = to.parse().expect("Invalid to address");
                 log::info!("Minting {} tokens to {:?}", amount, to);
 
-                // Only master chain can mint
                 let params = self.runtime.application_parameters();
-                if self.runtime.chain_id() != params.master_chain {
-                    panic!("Only master chain can mint tokens");
+                let signer = self.runtime.authenticated_signer();
+                if !signer.is_some_and(|signer| params.minters.contains(&signer)) {
+                    return Err(TokenError::NotMinter);
                 }
 
-                // Update balance
-                let current_balance = self
-                    .state
-                    .accounts
-                    .get(&to)
-                    .await
-                    .expect("Failed to get balance")
-                    .unwrap_or(Amount::ZERO);
+                self.increase_supply(amount)?;
+                self.credit(to, amount).await;
+
+                let total_supply = *self.state.total_supply.get();
+                self.record(TokenEvent::Mint {
+                    to,
+                    amount,
+                    total_supply,
+                })
+                .await;
+                log::info!("Minted {} tokens, new total supply: {}", amount, total_supply);
+                Ok(TokenResponse::Ok)
+            }
 
-                self.state
-                    .accounts
-                    .insert(&to, current_balance.saturating_add(amount))
-                    .expect("Failed to update balance");
+            TokenOperation::Burn { owner, amount } => {
+                self.check_permission(owner)?;
+                log::info!("Burning {} tokens from {:?}", amount, owner);
+
+                self.debit(owner, amount).await?;
+                self.destroy_supply(amount);
+                let total_supply = *self.state.total_supply.get();
+
+                self.record(TokenEvent::Burn {
+                    owner,
+                    amount,
+                    total_supply,
+                })
+                .await;
+                log::info!("Burned {} tokens, new total supply: {}", amount, total_supply);
+                Ok(TokenResponse::Ok)
+            }
 
-                // Update total supply
-                let total = self.state.total_supply.get_mut();
-                *total = total.saturating_add(amount);
+            TokenOperation::ClaimBonus => {
+                let owner = self.runtime.authenticated_signer().ok_or(TokenError::NotSigned)?;
+                let master_chain = self.runtime.application_parameters().master_chain;
+                if self.runtime.chain_id() != master_chain {
+                    // Only the master chain creates tokens. It pays the bonus to this chain.
+                    log::info!("Forwarding daily bonus claim for {:?} to the master chain", owner);
+                    self.runtime
+                        .prepare_message(TokenMessage::ClaimBonus { owner })
+                        .with_authentication()
+                        .send_to(master_chain);
+                    return Ok(TokenResponse::Ok);
+                }
 
-                log::info!("Minted {} tokens, new total supply: {}", amount, *total);
-                TokenResponse::Ok
+                log::info!("Claiming daily bonus for {:?}", owner);
+                let chain_id = self.runtime.chain_id();
+                self.claim_bonus(owner, Account { chain_id, owner }).await?;
+                Ok(TokenResponse::Balance(self.balance(&owner).await))
             }
+        }
+    }
 
-            TokenOperation::ClaimBonus { owner } => {
-                let owner: AccountOwner = owner.parse().expect("Invalid owner");
-                log::info!("Claiming daily bonus for {:?}", owner);
+    /// Pays `owner`'s daily bonus to `to`, creating the tokens on this chain.
+    async fn claim_bonus(&mut self, owner: AccountOwner, to: Account) -> Result<(), TokenError> {
+        let params = self.runtime.application_parameters();
+        let current_time = self.runtime.system_time().micros();
 
-                let params = self.runtime.application_parameters();
-                let current_time = self.runtime.system_time().micros();
+        let mut bonus = self
+            .state
+            .daily_bonuses
+            .get(&owner)
+            .await
+            .expect("Failed to get daily bonus")
+            .unwrap_or_default();
 
-                // Get or create daily bonus record
-                let mut bonus = self
-                    .state
-                    .daily_bonuses
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get daily bonus")
-                    .unwrap_or(DailyBonus {
-                        amount: params.daily_bonus,
-                        last_claim: 0,
-                    });
+        let mut streak = self
+            .state
+            .bonus_streaks
+            .get(&owner)
+            .await
+            .expect("Failed to get bonus streak")
+            .unwrap_or_default();
 
-                // Attempt to claim
-                let claimed_amount = bonus.claim(current_time);
+        let claimed_amount = bonus.claim(&mut streak, current_time, &params.bonus_schedule);
+        if claimed_amount == Amount::ZERO {
+            return Err(TokenError::BonusNotAvailable);
+        }
 
-                if claimed_amount == Amount::ZERO {
-                    panic!("Daily bonus not available yet");
-                }
+        self.increase_supply(claimed_amount)?;
+        self.state
+            .bonus_streaks
+            .insert(&owner, streak)
+            .expect("Failed to update bonus streak");
+        let position = self.state.bonus_claims.count() as u32;
+        add_to_index(&mut self.state.bonus_claim_index, owner, position).await;
+        self.state.bonus_claims.push(BonusClaim {
+            owner,
+            amount: claimed_amount,
+            streak,
+            claimed_at: current_time,
+        });
+        self.state
+            .daily_bonuses
+            .insert(&owner, bonus)
+            .expect("Failed to update daily bonus");
+
+        let total_supply = *self.state.total_supply.get();
+        self.record(TokenEvent::BonusClaimed {
+            owner,
+            amount: claimed_amount,
+            streak,
+            total_supply,
+        })
+        .await;
+        self.deliver(owner, to, claimed_amount).await;
+
+        log::info!("Claimed {} tokens", claimed_amount);
+        Ok(())
+    }
 
-                // Update bonus record
-                self.state
-                    .daily_bonuses
-                    .insert(&owner, bonus)
-                    .expect("Failed to update daily bonus");
+    /// The calling application, which must be authorized to move balances.
+    fn authorized_caller(&mut self) -> Result<ApplicationId, TokenError> {
+        let caller = self
+            .runtime
+            .authenticated_caller_id()
+            .ok_or(TokenError::UnauthorizedApp)?;
+        if !self.state.authorized_apps.get().contains(&caller) {
+            return Err(TokenError::UnauthorizedApp);
+        }
+        Ok(caller)
+    }
 
-                // Update balance
-                let current_balance = self
-                    .state
-                    .accounts
-                    .get(&owner)
-                    .await
-                    .expect("Failed to get balance")
-                    .unwrap_or(Amount::ZERO);
+    fn check_owner(&mut self) -> Result<(), TokenError> {
+        let params = self.runtime.application_parameters();
+        if self.runtime.authenticated_signer() != Some(params.owner) {
+            return Err(TokenError::NotTokenOwner);
+        }
+        Ok(())
+    }
 
-                self.state
-                    .accounts
-                    .insert(&owner, current_balance.saturating_add(claimed_amount))
-                    .expect("Failed to update balance");
+    /// Fails unless `owner` is the authenticated signer or the calling application.
+    fn check_permission(&mut self, owner: AccountOwner) -> Result<(), TokenError> {
+        let signer = self.runtime.authenticated_signer();
+        let caller = self.runtime.authenticated_caller_id().map(AccountOwner::from);
+        if signer != Some(owner) && caller != Some(owner) {
+            return Err(TokenError::NotPermitted(owner));
+        }
+        Ok(())
+    }
 
-                log::info!("Claimed {} tokens", claimed_amount);
-                TokenResponse::Balance(current_balance.saturating_add(claimed_amount))
-            }
+    async fn balance(&self, owner: &AccountOwner) -> Amount {
+        self.state
+            .accounts
+            .get(owner)
+            .await
+            .expect("Failed to get balance")
+            .unwrap_or(Amount::ZERO)
+    }
+
+    async fn debit(&mut self, owner: AccountOwner, amount: Amount) -> Result<(), TokenError> {
+        let balance = self.balance(&owner).await;
+        if balance < amount {
+            return Err(TokenError::InsufficientBalance);
         }
+        self.state
+            .accounts
+            .insert(&owner, balance.saturating_sub(amount))
+            .expect("Failed to update balance");
+        self.remove_held(amount);
+        Ok(())
     }
 
-    async fn execute_message(&mut self, message: Self::Message) {
-        match message {
-            TokenMessage::BalanceUpdated { owner, new_balance } => {
-                log::info!("Received balance update for {:?}: {}", owner, new_balance);
+    /// Debits `from` and credits `to`, sending a tracked message when `to` is on another
+    /// chain so a rejected credit bounces back and refunds the sender.
+    async fn transfer(&mut self, from: AccountOwner, to: Account, amount: Amount) -> Result<(), TokenError> {
+        self.debit(from, amount).await?;
+        self.record(TokenEvent::Transfer { from, to, amount }).await;
+        self.deliver(from, to, amount).await;
+        Ok(())
+    }
 
-                self.state
-                    .accounts
-                    .insert(&owner, new_balance)
-                    .expect("Failed to update balance from message");
-            }
+    /// Credits tokens that already left `from` to `to`, on this chain or through a tracked
+    /// message that refunds `from` if it bounces.
+    async fn deliver(&mut self, from: AccountOwner, to: Account, amount: Amount) {
+        if to.chain_id == self.runtime.chain_id() {
+            self.credit(to.owner, amount).await;
+            log::info!("Transfer completed");
+        } else {
+            self.decrease_supply(amount);
+            self.runtime
+                .prepare_message(TokenMessage::Credit {
+                    target: to.owner,
+                    amount,
+                    source: from,
+                })
+                .with_authentication()
+                .with_tracking()
+                .send_to(to.chain_id);
+            log::info!("Transfer sent to chain {:?}", to.chain_id);
+        }
+    }
 
-            TokenMessage::TokensMinted { to, amount } => {
-                log::info!("Received tokens minted notification: {} to {:?}", amount, to);
-            }
+    /// Emits `event` on the token stream and appends it to the history.
+    async fn record(&mut self, event: TokenEvent) {
+        let timestamp = self.runtime.system_time().micros();
+        self.runtime.emit(StreamName::from(TOKEN_STREAM), &event);
+        let position = self.state.history.count() as u32;
+        for owner in event.owners() {
+            add_to_index(&mut self.state.history_index, owner, position).await;
         }
+        self.state.history.push(TokenRecord { event, timestamp });
     }
 
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
+    /// Adds newly created tokens to the supply. Only the master chain creates tokens, so
+    /// its count of issued tokens caps the supply across all chains.
+    fn increase_supply(&mut self, amount: Amount) -> Result<(), TokenError> {
+        let params = self.runtime.application_parameters();
+        if self.runtime.chain_id() != params.master_chain {
+            return Err(TokenError::NotMasterChain);
+        }
+        let issued = self.state.issued.get().saturating_add(amount);
+        if issued > params.max_supply {
+            return Err(TokenError::MaxSupplyExceeded);
+        }
+        self.state.issued.set(issued);
+        let total = self.state.total_supply.get_mut();
+        *total = total.saturating_add(amount);
+        Ok(())
+    }
+
+    /// Removes tokens that were sent to another chain from this chain's supply.
+    fn decrease_supply(&mut self, amount: Amount) {
+        let total = self.state.total_supply.get_mut();
+        *total = total.saturating_sub(amount);
     }
+
+    /// Removes destroyed tokens from the supply. Only tokens destroyed on the master chain
+    /// free room under the max supply; elsewhere the cap stays conservative.
+    fn destroy_supply(&mut self, amount: Amount) {
+        self.decrease_supply(amount);
+        if self.runtime.chain_id() == self.runtime.application_parameters().master_chain {
+            let issued = self.state.issued.get_mut();
+            *issued = issued.saturating_sub(amount);
+        }
+    }
+
+    async fn credit(&mut self, owner: AccountOwner, amount: Amount) {
+        let balance = self.balance(&owner).await;
+        self.state
+            .accounts
+            .insert(&owner, balance.saturating_add(amount))
+            .expect("Failed to update balance");
+        self.add_held(amount);
+    }
+
+    fn add_held(&mut self, amount: Amount) {
+        let held = self.state.held.get_mut();
+        *held = held.saturating_add(amount);
+    }
+
+    fn remove_held(&mut self, amount: Amount) {
+        let held = self.state.held.get_mut();
+        *held = held.saturating_sub(amount);
+    }
+
+    /// Panics if balances and escrows hold more tokens than this chain's supply, which
+    /// rolls back the whole transaction. Runs after every operation and message.
+    fn check_supply(&self) {
+        let held = *self.state.held.get();
+        let supply = *self.state.total_supply.get();
+        if held > supply {
+            panic!("{}", TokenError::SupplyExceeded { held, supply });
+        }
+    }
+}
+
+/// Appends `position` to `owner`'s entry in a per-account index.
+async fn add_to_index(index: &mut MapView<AccountOwner, Vec<u32>>, owner: AccountOwner, position: u32) {
+    let mut positions = index.get(&owner).await.expect("Failed to get index").unwrap_or_default();
+    positions.push(position);
+    index.insert(&owner, positions).expect("Failed to update index");
 }
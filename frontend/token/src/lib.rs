@@ -1,10 +1,12 @@
-use async_graphql::{Request, Response, SimpleObject};
-use linera_sdk::linera_base_types::{AccountOwner, Amount, ChainId};
+use async_graphql::{Enum, Request, Response, SimpleObject};
+use linera_sdk::linera_base_types::{Account, AccountOwner, Amount, ApplicationId, ChainId};
 use linera_sdk::{
+    abis::fungible::{self, FungibleOperation, FungibleResponse, FungibleTokenAbi},
     graphql::GraphQLMutationRoot,
     linera_base_types::{ContractAbi, ServiceAbi},
 };
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TokenAbi;
@@ -19,73 +21,451 @@ impl ServiceAbi for TokenAbi {
     type QueryResponse = Response;
 }
 
+/// The first six variants have the same layout as `FungibleOperation`, so applications
+/// holding an `ApplicationId<FungibleTokenAbi>` for this token can call it directly.
+/// Keep them first, in this order, with these field types.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum TokenOperation {
-    /// Get account balance
-    Balance { owner: String },
+    /// Get account balance. No contract in this repo calls it; it keeps the layout of
+    /// `FungibleOperation` so `FungibleTokenAbi` callers can use this token.
+    Balance { owner: AccountOwner },
 
-    /// Update account balance (called by market app)
-    UpdateBalance { owner: String, amount: Amount },
+    /// Get the ticker symbol
+    TickerSymbol,
 
-    /// Transfer tokens between accounts
+    /// Allow `spender` (an account or application) to transfer up to `allowance` of `owner`'s tokens
+    Approve {
+        owner: AccountOwner,
+        spender: AccountOwner,
+        allowance: Amount,
+    },
+
+    /// Transfer tokens to an account on this or another chain
     Transfer {
-        from: String,
-        to: String,
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    },
+
+    /// Transfer tokens from `owner` to an account on this or another chain using `spender`'s allowance
+    TransferFrom {
+        owner: AccountOwner,
+        spender: AccountOwner,
         amount: Amount,
+        target_account: Account,
     },
 
-    /// Mint tokens (master chain only)
-    Mint { to: String, amount: Amount },
+    /// Transfer tokens from an account on this or another chain
+    Claim {
+        source_account: Account,
+        amount: Amount,
+        target_account: Account,
+    },
+
+    /// Destroy tokens from an account, reducing the total supply (authorized apps only,
+    /// fails if funds are insufficient)
+    Debit { owner: AccountOwner, amount: Amount },
+
+    /// Create tokens for an account, counted against the max supply like `Mint` (authorized
+    /// apps on the master chain only). Apps paying out tokens they took from players use `Escrow` and `Release`.
+    Credit { owner: AccountOwner, amount: Amount },
+
+    /// Move tokens from an account into the calling app's escrow (authorized apps only)
+    Escrow { owner: AccountOwner, amount: Amount },
+
+    /// Pay tokens out of the calling app's escrow to an account (authorized apps only)
+    Release { to: AccountOwner, amount: Amount },
 
-    /// Claim daily bonus
-    ClaimBonus { owner: String },
+    /// Allow an application to call Debit, Credit, Escrow and Release (owner only)
+    AuthorizeApp { app_id: ApplicationId },
+
+    /// Revoke an application's authorization (owner only)
+    RevokeApp { app_id: ApplicationId },
+
+    /// Mint tokens (minters on the master chain only, up to the max supply)
+    Mint { to: AccountOwner, amount: Amount },
+
+    /// Destroy tokens from an account, reducing the total supply
+    Burn { owner: AccountOwner, amount: Amount },
+
+    /// Claim the signer's daily bonus. Off the master chain, the claim is forwarded there
+    /// and the bonus arrives in a `Credit` message.
+    ClaimBonus,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum TokenMessage {
-    /// Balance updated notification
-    BalanceUpdated { owner: AccountOwner, new_balance: Amount },
+    /// Credit tokens sent from another chain. Bounces back to `source` if rejected.
+    Credit {
+        target: AccountOwner,
+        amount: Amount,
+        source: AccountOwner,
+    },
+
+    /// Withdraw tokens from `owner` on this chain and transfer them to `target_account`
+    Withdraw {
+        owner: AccountOwner,
+        amount: Amount,
+        target_account: Account,
+    },
 
     /// Tokens minted notification
     TokensMinted { to: AccountOwner, amount: Amount },
+
+    /// Claim `owner`'s daily bonus on the master chain and pay it to the sending chain
+    ClaimBonus { owner: AccountOwner },
 }
 
+/// The first three variants have the same layout as `FungibleResponse`.
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub enum TokenResponse {
     #[default]
     Ok,
     Balance(Amount),
+    TickerSymbol(String),
+    /// The operation failed without changing anything. Only returned to calling
+    /// applications; operations submitted by users panic instead.
+    Error(TokenError),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Error)]
+pub enum TokenError {
+    #[error("Insufficient balance")]
+    InsufficientBalance,
+    #[error("Insufficient allowance")]
+    InsufficientAllowance,
+    #[error("Insufficient escrow")]
+    InsufficientEscrow,
+    #[error("Daily bonus not available yet")]
+    BonusNotAvailable,
+    #[error("Max supply exceeded")]
+    MaxSupplyExceeded,
+    #[error("Operation must be signed")]
+    NotSigned,
+    #[error("Not authorized to act for {0}")]
+    NotPermitted(AccountOwner),
+    #[error("Caller application is not authorized")]
+    UnauthorizedApp,
+    #[error("Only token owner can manage authorized applications")]
+    NotTokenOwner,
+    #[error("Only the master chain can create tokens")]
+    NotMasterChain,
+    #[error("Only minters can mint tokens")]
+    NotMinter,
+    #[error("Balances and escrows hold {held} tokens, more than the supply of {supply}")]
+    SupplyExceeded { held: Amount, supply: Amount },
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TokenParameters {
+    /// Display name, e.g. "Flash Token"
+    pub name: String,
+    /// Ticker symbol, e.g. "FLASH"
+    pub ticker_symbol: String,
+    /// Decimal places wallets should display. Amounts always carry 18 internally.
+    pub decimals: u8,
     pub master_chain: ChainId,
+    /// Token owner (admin), who manages authorized applications
+    pub owner: AccountOwner,
     pub initial_supply: Amount,
-    pub daily_bonus: Amount,
+    /// Cap on tokens created and not destroyed again, across all chains. Tokens are only
+    /// created on the master chain, which checks the cap on every mint, credit and bonus.
+    /// Tokens destroyed on other chains do not free room under it.
+    pub max_supply: Amount,
+    /// Owners allowed to mint
+    pub minters: Vec<AccountOwner>,
+    pub bonus_schedule: BonusSchedule,
 }
 
+/// Name of the stream token events are emitted on.
+pub const TOKEN_STREAM: &str = "token_events";
+
+/// A balance or supply change, emitted on `TOKEN_STREAM` and kept in the chain's history.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TokenEvent {
+    /// Tokens were sent from an account on this chain.
+    Transfer {
+        from: AccountOwner,
+        to: Account,
+        amount: Amount,
+    },
+    /// Tokens sent from another chain were credited, or a bounced transfer was refunded.
+    Received {
+        from: Account,
+        to: AccountOwner,
+        amount: Amount,
+    },
+    /// Tokens were minted. `total_supply` is the chain's supply afterwards.
+    Mint {
+        to: AccountOwner,
+        amount: Amount,
+        total_supply: Amount,
+    },
+    /// Tokens were burned.
+    Burn {
+        owner: AccountOwner,
+        amount: Amount,
+        total_supply: Amount,
+    },
+    /// A daily bonus created new tokens.
+    BonusClaimed {
+        owner: AccountOwner,
+        amount: Amount,
+        streak: u32,
+        total_supply: Amount,
+    },
+    /// An authorized application destroyed tokens of an account.
+    Debited {
+        app: ApplicationId,
+        owner: AccountOwner,
+        amount: Amount,
+    },
+    /// An authorized application created tokens for an account.
+    Credited {
+        app: ApplicationId,
+        owner: AccountOwner,
+        amount: Amount,
+    },
+    /// Tokens moved from an account into an application's escrow.
+    Escrowed {
+        app: ApplicationId,
+        owner: AccountOwner,
+        amount: Amount,
+    },
+    /// Tokens were paid out of an application's escrow.
+    Released {
+        app: ApplicationId,
+        to: AccountOwner,
+        amount: Amount,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, Enum)]
+pub enum TokenEventKind {
+    Transfer,
+    Received,
+    Mint,
+    Burn,
+    BonusClaimed,
+    Debited,
+    Credited,
+    Escrowed,
+    Released,
+}
+
+/// A `TokenEvent` with the time it happened.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TokenRecord {
+    pub event: TokenEvent,
+    pub timestamp: u64, // Timestamp in micros
+}
+
+/// One balance change of an account, as shown in its history.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct HistoryEntry {
+    pub kind: TokenEventKind,
+    pub amount: Amount,
+    /// Whether the tokens left the account
+    pub outgoing: bool,
+    /// The other account or application involved, if any
+    pub counterparty: Option<String>,
+    pub timestamp: u64, // Timestamp in micros
+}
+
+impl TokenEvent {
+    /// The accounts whose history shows this event.
+    pub fn owners(&self) -> Vec<AccountOwner> {
+        match self {
+            TokenEvent::Transfer { from, to, .. } if *from == to.owner => vec![*from],
+            TokenEvent::Transfer { from, to, .. } => vec![*from, to.owner],
+            TokenEvent::Received { to, .. } | TokenEvent::Mint { to, .. } | TokenEvent::Released { to, .. } => {
+                vec![*to]
+            }
+            TokenEvent::Burn { owner, .. }
+            | TokenEvent::BonusClaimed { owner, .. }
+            | TokenEvent::Debited { owner, .. }
+            | TokenEvent::Credited { owner, .. }
+            | TokenEvent::Escrowed { owner, .. } => vec![*owner],
+        }
+    }
+}
+
+impl TokenRecord {
+    /// How this record changed `owner`'s balance, or `None` if it did not involve them.
+    pub fn entry_for(&self, owner: &AccountOwner) -> Option<HistoryEntry> {
+        let (kind, amount, outgoing, counterparty) = match &self.event {
+            TokenEvent::Transfer { from, to, amount } if from == owner => {
+                (TokenEventKind::Transfer, *amount, true, Some(to.to_string()))
+            }
+            TokenEvent::Transfer { from, to, amount } if to.owner == *owner => {
+                (TokenEventKind::Transfer, *amount, false, Some(from.to_string()))
+            }
+            TokenEvent::Received { from, to, amount } if to == owner => {
+                (TokenEventKind::Received, *amount, false, Some(from.to_string()))
+            }
+            TokenEvent::Mint { to, amount, .. } if to == owner => (TokenEventKind::Mint, *amount, false, None),
+            TokenEvent::Burn { owner: burner, amount, .. } if burner == owner => {
+                (TokenEventKind::Burn, *amount, true, None)
+            }
+            TokenEvent::BonusClaimed { owner: claimer, amount, .. } if claimer == owner => {
+                (TokenEventKind::BonusClaimed, *amount, false, None)
+            }
+            TokenEvent::Debited { app, owner: debited, amount } if debited == owner => {
+                (TokenEventKind::Debited, *amount, true, Some(app.to_string()))
+            }
+            TokenEvent::Credited { app, owner: credited, amount } if credited == owner => {
+                (TokenEventKind::Credited, *amount, false, Some(app.to_string()))
+            }
+            TokenEvent::Escrowed { app, owner: escrowed, amount } if escrowed == owner => {
+                (TokenEventKind::Escrowed, *amount, true, Some(app.to_string()))
+            }
+            TokenEvent::Released { app, to, amount } if to == owner => {
+                (TokenEventKind::Released, *amount, false, Some(app.to_string()))
+            }
+            _ => return None,
+        };
+        Some(HistoryEntry {
+            kind,
+            amount,
+            outgoing,
+            counterparty,
+            timestamp: self.timestamp,
+        })
+    }
+}
+
+/// How daily bonuses are paid out.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BonusSchedule {
+    /// Bonus for the first claim of a streak
+    pub base: Amount,
+    /// Each consecutive claim pays `base` times the streak length, up to this multiplier
+    pub max_multiplier: u32,
+    /// Time between claims. A streak resets once a second cooldown passes without a claim.
+    pub cooldown_micros: u64,
+}
+
+impl From<FungibleOperation> for TokenOperation {
+    fn from(operation: FungibleOperation) -> Self {
+        match operation {
+            FungibleOperation::Balance { owner } => TokenOperation::Balance { owner },
+            FungibleOperation::TickerSymbol => TokenOperation::TickerSymbol,
+            FungibleOperation::Approve {
+                owner,
+                spender,
+                allowance,
+            } => TokenOperation::Approve {
+                owner,
+                spender,
+                allowance,
+            },
+            FungibleOperation::Transfer {
+                owner,
+                amount,
+                target_account,
+            } => TokenOperation::Transfer {
+                owner,
+                amount,
+                target_account: account(target_account),
+            },
+            FungibleOperation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account,
+            } => TokenOperation::TransferFrom {
+                owner,
+                spender,
+                amount,
+                target_account: account(target_account),
+            },
+            FungibleOperation::Claim {
+                source_account,
+                amount,
+                target_account,
+            } => TokenOperation::Claim {
+                source_account: account(source_account),
+                amount,
+                target_account: account(target_account),
+            },
+        }
+    }
+}
+
+/// The fungible ABI has its own `Account` type with the same fields and layout.
+fn account(account: fungible::Account) -> Account {
+    Account {
+        chain_id: account.chain_id,
+        owner: account.owner,
+    }
+}
+
+/// `FungibleResponse` has no error variant, so errors are returned as `Err`.
+impl TryFrom<TokenResponse> for FungibleResponse {
+    type Error = TokenError;
+
+    fn try_from(response: TokenResponse) -> Result<Self, TokenError> {
+        match response {
+            TokenResponse::Ok => Ok(FungibleResponse::Ok),
+            TokenResponse::Balance(amount) => Ok(FungibleResponse::Balance(amount)),
+            TokenResponse::TickerSymbol(symbol) => Ok(FungibleResponse::TickerSymbol(symbol)),
+            TokenResponse::Error(error) => Err(error),
+        }
+    }
+}
+
+/// The token's id typed with the standard fungible ABI, for apps and tools that only
+/// speak `FungibleTokenAbi`.
+pub fn fungible_id(app_id: ApplicationId<TokenAbi>) -> ApplicationId<FungibleTokenAbi> {
+    app_id.forget_abi().with_abi()
+}
+
+/// Stored per user with the layout it had before streaks existed; the streak is kept
+/// next to it in the state.
 #[derive(Debug, Clone, Default, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
 pub struct DailyBonus {
+    /// Amount paid by the last claim
     pub amount: Amount,
     pub last_claim: u64, // Timestamp in micros
 }
 
 impl DailyBonus {
-    pub const ONE_DAY_MICROS: u64 = 86_400_000_000;
+    pub const ONE_DAY_MICROS: u64 = abi::ONE_DAY_MICROS;
 
-    pub fn can_claim(&self, current_time_micros: u64) -> bool {
+    pub fn can_claim(&self, current_time_micros: u64, schedule: &BonusSchedule) -> bool {
         if self.last_claim == 0 {
             return true;
         }
-        current_time_micros.saturating_sub(self.last_claim) >= Self::ONE_DAY_MICROS
+        current_time_micros.saturating_sub(self.last_claim) >= schedule.cooldown_micros
     }
 
-    pub fn claim(&mut self, current_time_micros: u64) -> Amount {
-        if self.can_claim(current_time_micros) {
-            self.last_claim = current_time_micros;
-            self.amount
-        } else {
-            Amount::ZERO
+    /// Claims the bonus, extending `streak` if the last claim was within two cooldowns
+    /// and restarting it otherwise. Returns zero if the cooldown has not passed yet.
+    pub fn claim(&mut self, streak: &mut u32, current_time_micros: u64, schedule: &BonusSchedule) -> Amount {
+        if !self.can_claim(current_time_micros, schedule) {
+            return Amount::ZERO;
         }
+        let elapsed = current_time_micros.saturating_sub(self.last_claim);
+        *streak = if self.last_claim != 0 && elapsed < schedule.cooldown_micros.saturating_mul(2) {
+            streak.saturating_add(1)
+        } else {
+            1
+        };
+        let multiplier = (*streak).min(schedule.max_multiplier.max(1));
+        self.amount = schedule
+            .base
+            .try_mul(multiplier as u128)
+            .expect("Bonus amount overflow");
+        self.last_claim = current_time_micros;
+        self.amount
     }
 }
+
+/// One paid daily bonus.
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq, Serialize, SimpleObject)]
+pub struct BonusClaim {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    pub streak: u32,
+    pub claimed_at: u64, // Timestamp in micros
+}
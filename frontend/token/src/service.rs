@@ -6,12 +6,12 @@ use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
     abi::WithServiceAbi,
     graphql::GraphQLMutationRoot,
-    linera_base_types::{AccountOwner, Amount},
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
     views::{MapView, View},
     Service, ServiceRuntime,
 };
 use std::sync::Arc;
-use token::{DailyBonus, TokenOperation};
+use token::{BonusClaim, DailyBonus, HistoryEntry, TokenOperation};
 use self::state::TokenState;
 
 #[derive(Clone)]
@@ -57,11 +57,98 @@ impl TokenService {
         &self.state.accounts
     }
 
+    async fn balance(&self, owner: AccountOwner) -> Amount {
+        self.state
+            .accounts
+            .get(&owner)
+            .await
+            .expect("Failed to get balance")
+            .unwrap_or(Amount::ZERO)
+    }
+
     async fn daily_bonuses(&self) -> &MapView<AccountOwner, DailyBonus> {
         &self.state.daily_bonuses
     }
 
+    /// Consecutive daily bonus claims of `owner`, including their last one
+    async fn bonus_streak(&self, owner: AccountOwner) -> u32 {
+        self.state
+            .bonus_streaks
+            .get(&owner)
+            .await
+            .expect("Failed to get bonus streak")
+            .unwrap_or_default()
+    }
+
+    async fn name(&self) -> String {
+        self.runtime.application_parameters().name
+    }
+
+    async fn ticker_symbol(&self) -> String {
+        self.runtime.application_parameters().ticker_symbol
+    }
+
+    async fn decimals(&self) -> u8 {
+        self.runtime.application_parameters().decimals
+    }
+
+    /// Daily bonuses paid to `owner`, newest first
+    async fn bonus_claims(&self, owner: AccountOwner, offset: Option<u32>, limit: Option<u32>) -> Vec<BonusClaim> {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(20) as usize;
+        let positions = self.state.bonus_claim_index.get(&owner).await.ok().flatten().unwrap_or_default();
+        let mut claims = Vec::new();
+        for position in positions.into_iter().rev().skip(offset).take(limit) {
+            if let Ok(Some(claim)) = self.state.bonus_claims.get(position as usize).await {
+                claims.push(claim);
+            }
+        }
+        claims
+    }
+
+    /// Balance changes of `owner` on this chain, newest first
+    async fn history(&self, owner: AccountOwner, offset: Option<u32>, limit: Option<u32>) -> Vec<HistoryEntry> {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(20) as usize;
+        let positions = self.state.history_index.get(&owner).await.ok().flatten().unwrap_or_default();
+        let mut entries = Vec::new();
+        for position in positions.into_iter().rev().skip(offset).take(limit) {
+            if let Ok(Some(record)) = self.state.history.get(position as usize).await {
+                entries.extend(record.entry_for(&owner));
+            }
+        }
+        entries
+    }
+
     async fn total_supply(&self) -> &Amount {
         self.state.total_supply.get()
     }
+
+    /// Tokens in balances and escrows on this chain, never more than `total_supply`
+    async fn held(&self) -> &Amount {
+        self.state.held.get()
+    }
+
+    /// Tokens created and not destroyed again across all chains, as counted on the master chain
+    async fn issued(&self) -> &Amount {
+        self.state.issued.get()
+    }
+
+    /// How much `spender` may still transfer from `owner`
+    async fn allowance(&self, owner: AccountOwner, spender: AccountOwner) -> Amount {
+        self.state
+            .allowances
+            .get(&(owner, spender))
+            .await
+            .expect("Failed to get allowance")
+            .unwrap_or(Amount::ZERO)
+    }
+
+    async fn authorized_apps(&self) -> &Vec<ApplicationId> {
+        self.state.authorized_apps.get()
+    }
+
+    async fn escrows(&self) -> &MapView<ApplicationId, Amount> {
+        &self.state.escrows
+    }
 }
@@ -1,6 +1,6 @@
-use token::DailyBonus;
-use linera_sdk::linera_base_types::{AccountOwner, Amount};
-use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
+use token::{BonusClaim, DailyBonus, TokenRecord};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, ApplicationId};
+use linera_sdk::views::{linera_views, LogView, MapView, RegisterView, RootView, ViewStorageContext};
 
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
@@ -11,6 +11,39 @@ pub struct TokenState {
     /// Daily bonus state per user
     pub daily_bonuses: MapView<AccountOwner, DailyBonus>,
 
-    /// Total supply
+    /// Tokens that live on this chain: created here or received from other chains,
+    /// minus those burned or sent away. Balances and escrows never add up to more,
+    /// which the contract checks against `held` after every operation and message.
     pub total_supply: RegisterView<Amount>,
+
+    /// Applications allowed to debit, credit and escrow balances
+    pub authorized_apps: RegisterView<Vec<ApplicationId>>,
+
+    /// Remaining allowances ((owner, spender) -> amount)
+    pub allowances: MapView<(AccountOwner, AccountOwner), Amount>,
+
+    /// Tokens held in escrow per application
+    pub escrows: MapView<ApplicationId, Amount>,
+
+    /// Every balance and supply change on this chain, oldest first
+    pub history: LogView<TokenRecord>,
+
+    /// Every paid daily bonus, oldest first
+    pub bonus_claims: LogView<BonusClaim>,
+
+    /// Consecutive daily bonus claims per user, including their last one
+    pub bonus_streaks: MapView<AccountOwner, u32>,
+
+    /// Tokens created and not destroyed again, across all chains. Only the master chain
+    /// creates tokens, so only its count is kept up to date and checked against the max supply.
+    pub issued: RegisterView<Amount>,
+
+    /// Positions in `history` of the records involving each account, oldest first
+    pub history_index: MapView<AccountOwner, Vec<u32>>,
+
+    /// Positions in `bonus_claims` of each account's claims, oldest first
+    pub bonus_claim_index: MapView<AccountOwner, Vec<u32>>,
+
+    /// Sum of all balances and escrows on this chain
+    pub held: RegisterView<Amount>,
 }
@@ -0,0 +1,46 @@
+//! Integration tests for spending allowances with TransferFrom.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{amount_of, query, token_chain};
+use linera_sdk::linera_base_types::{Account, Amount};
+use token::TokenOperation;
+
+/// A TransferFrom that fails on the owner's balance leaves the allowance unspent.
+#[tokio::test]
+async fn test_failed_transfer_keeps_allowance() {
+    let (_validator, app_id, chain, owner) = token_chain().await;
+    let target_account = Account { chain_id: chain.id(), owner };
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                TokenOperation::Approve { owner, spender: owner, allowance: Amount::from_tokens(20) },
+            );
+        })
+        .await;
+
+    let result = chain.try_add_block(|block| {
+        block.with_operation(
+            app_id,
+            TokenOperation::TransferFrom { owner, spender: owner, amount: Amount::from_tokens(15), target_account },
+        );
+    });
+    assert!(result.await.is_err());
+    let allowance = format!("query {{ allowance(owner: \"{owner}\", spender: \"{owner}\") }}");
+    let response = query(&chain, app_id, &allowance).await;
+    assert_eq!(amount_of(&response["allowance"]), Amount::from_tokens(20));
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                TokenOperation::TransferFrom { owner, spender: owner, amount: Amount::from_tokens(4), target_account },
+            );
+        })
+        .await;
+    let response = query(&chain, app_id, &allowance).await;
+    assert_eq!(amount_of(&response["allowance"]), Amount::from_tokens(16));
+}
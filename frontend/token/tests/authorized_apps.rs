@@ -0,0 +1,58 @@
+//! Integration tests for the operations only authorized applications may call.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{amount_of, query, token_chain};
+use linera_sdk::linera_base_types::{AccountOwner, Amount};
+use token::TokenOperation;
+
+/// Debit, Credit, Escrow and Release fail when a user submits them, even the token owner,
+/// and leave balances and supply untouched.
+#[tokio::test]
+async fn test_users_cannot_move_balances() {
+    let (_validator, app_id, chain, owner) = token_chain().await;
+    let amount = Amount::from_tokens(1);
+    let operations: [fn(AccountOwner, Amount) -> TokenOperation; 4] = [
+        |owner, amount| TokenOperation::Debit { owner, amount },
+        |owner, amount| TokenOperation::Credit { owner, amount },
+        |owner, amount| TokenOperation::Escrow { owner, amount },
+        |to, amount| TokenOperation::Release { to, amount },
+    ];
+    for operation in operations {
+        let result = chain
+            .try_add_block(|block| {
+                block.with_operation(app_id, operation(owner, amount));
+            })
+            .await;
+        assert!(result.is_err());
+    }
+
+    let response = query(&chain, app_id, &format!("query {{ balance(owner: \"{owner}\") totalSupply }}")).await;
+    assert_eq!(amount_of(&response["balance"]), Amount::from_tokens(10));
+    assert_eq!(amount_of(&response["totalSupply"]), Amount::from_tokens(10));
+}
+
+/// Only the token owner may authorize an application.
+#[tokio::test]
+async fn test_authorize_app_is_owner_only() {
+    let (validator, app_id, chain, _owner) = token_chain().await;
+    let app = app_id.forget_abi();
+
+    let outsider = validator.new_chain().await;
+    let result = outsider
+        .try_add_block(|block| {
+            block.with_operation(app_id, TokenOperation::AuthorizeApp { app_id: app });
+        })
+        .await;
+    assert!(result.is_err());
+
+    chain
+        .add_block(|block| {
+            block.with_operation(app_id, TokenOperation::AuthorizeApp { app_id: app });
+        })
+        .await;
+    let response = query(&chain, app_id, "query { authorizedApps }").await;
+    assert_eq!(response["authorizedApps"][0], app.to_string());
+}
@@ -0,0 +1,52 @@
+//! Fixtures shared by the integration tests.
+
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount, ApplicationId},
+    serde_json,
+    test::{ActiveChain, QueryOutcome, TestValidator},
+};
+use token::{BonusSchedule, TokenAbi, TokenOperation, TokenParameters};
+
+/// Cap on the test token's supply.
+pub const MAX_SUPPLY: u128 = 100;
+
+/// Creates the token on a fresh chain whose owner is the token owner and only minter,
+/// and mints them ten tokens.
+pub async fn token_chain() -> (TestValidator, ApplicationId<TokenAbi>, ActiveChain, AccountOwner) {
+    let (validator, module_id) = TestValidator::with_current_module::<TokenAbi, TokenParameters, ()>().await;
+    let mut chain = validator.new_chain().await;
+    let owner = AccountOwner::from(chain.public_key());
+    let parameters = TokenParameters {
+        name: "Test Token".to_string(),
+        ticker_symbol: "TEST".to_string(),
+        decimals: 2,
+        master_chain: chain.id(),
+        owner,
+        initial_supply: Amount::ZERO,
+        max_supply: Amount::from_tokens(MAX_SUPPLY),
+        minters: vec![owner],
+        bonus_schedule: BonusSchedule {
+            base: Amount::from_tokens(1),
+            max_multiplier: 7,
+            cooldown_micros: abi::ONE_DAY_MICROS,
+        },
+    };
+    let app_id = chain.create_application(module_id, parameters, (), vec![]).await;
+    chain
+        .add_block(|block| {
+            block.with_operation(app_id, TokenOperation::Mint { to: owner, amount: Amount::from_tokens(10) });
+        })
+        .await;
+    (validator, app_id, chain, owner)
+}
+
+/// Runs a GraphQL query against the token service on `chain`.
+pub async fn query(chain: &ActiveChain, app_id: ApplicationId<TokenAbi>, query: &str) -> serde_json::Value {
+    let QueryOutcome { response, .. } = chain.graphql_query(app_id, query).await;
+    response
+}
+
+/// Parses an amount returned by the token service.
+pub fn amount_of(value: &serde_json::Value) -> Amount {
+    value.as_str().expect("Amounts are strings").parse().expect("Invalid amount")
+}
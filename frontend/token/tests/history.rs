@@ -0,0 +1,50 @@
+//! Integration tests for the per-account history and bonus claim pages.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{amount_of, query, token_chain};
+use linera_sdk::linera_base_types::{Account, AccountOwner, Amount};
+use token::TokenOperation;
+
+/// Each account's history only lists records involving it, newest first, one page at a time.
+#[tokio::test]
+async fn test_history_pages() {
+    let (validator, app_id, chain, owner) = token_chain().await;
+    let other = AccountOwner::from(validator.new_chain().await.public_key());
+    chain
+        .add_block(|block| {
+            for tokens in 1..=3 {
+                block.with_operation(
+                    app_id,
+                    TokenOperation::Transfer {
+                        owner,
+                        amount: Amount::from_tokens(tokens),
+                        target_account: Account { chain_id: chain.id(), owner: other },
+                    },
+                );
+            }
+            block.with_operation(app_id, TokenOperation::ClaimBonus);
+        })
+        .await;
+
+    let page = format!(
+        "query {{ history(owner: \"{owner}\", offset: 1, limit: 2) {{ kind outgoing }} \
+         bonusClaims(owner: \"{owner}\") {{ streak }} }}"
+    );
+    let response = query(&chain, app_id, &page).await;
+    let history = response["history"].as_array().expect("History is a list");
+    assert_eq!(history.len(), 2);
+    assert!(history.iter().all(|entry| entry["kind"] == "TRANSFER" && entry["outgoing"] == true));
+    assert_eq!(response["bonusClaims"][0]["streak"], 1);
+
+    let page = format!(
+        "query {{ balance(owner: \"{other}\") history(owner: \"{other}\") {{ outgoing }} \
+         bonusClaims(owner: \"{other}\") {{ streak }} }}"
+    );
+    let response = query(&chain, app_id, &page).await;
+    assert_eq!(amount_of(&response["balance"]), Amount::from_tokens(6));
+    assert_eq!(response["history"].as_array().map(Vec::len), Some(3));
+    assert_eq!(response["bonusClaims"].as_array().map(Vec::len), Some(0));
+}
@@ -0,0 +1,118 @@
+//! Integration tests for the max supply, which the master chain enforces for every chain.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{amount_of, query, token_chain, MAX_SUPPLY};
+use linera_sdk::{
+    linera_base_types::{Account, AccountOwner, Amount, ApplicationId},
+    test::ActiveChain,
+};
+use token::TokenAbi;
+use token::TokenOperation;
+
+/// Asserts that the balances and escrows on `chain` hold exactly its supply.
+async fn assert_held(chain: &ActiveChain, app_id: ApplicationId<TokenAbi>, expected: u128) {
+    let response = query(chain, app_id, "query { totalSupply held }").await;
+    assert_eq!(amount_of(&response["held"]), Amount::from_tokens(expected));
+    assert_eq!(amount_of(&response["totalSupply"]), Amount::from_tokens(expected));
+}
+
+/// Minting stops at the max supply, and burning on the master chain frees room again.
+#[tokio::test]
+async fn test_max_supply() {
+    let (_validator, app_id, chain, owner) = token_chain().await;
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                TokenOperation::Mint { to: owner, amount: Amount::from_tokens(MAX_SUPPLY - 10) },
+            );
+        })
+        .await;
+    let result = chain.try_add_block(|block| {
+        block.with_operation(app_id, TokenOperation::Mint { to: owner, amount: Amount::from_tokens(1) });
+    });
+    assert!(result.await.is_err());
+
+    chain
+        .add_block(|block| {
+            block.with_operation(app_id, TokenOperation::Burn { owner, amount: Amount::from_tokens(5) });
+            block.with_operation(app_id, TokenOperation::Mint { to: owner, amount: Amount::from_tokens(5) });
+        })
+        .await;
+    let response = query(&chain, app_id, "query { totalSupply issued }").await;
+    assert_eq!(amount_of(&response["totalSupply"]), Amount::from_tokens(MAX_SUPPLY));
+    assert_eq!(amount_of(&response["issued"]), Amount::from_tokens(MAX_SUPPLY));
+}
+
+/// A bonus claimed on another chain is created on the master chain, counted there, and
+/// sent back to the claiming chain.
+#[tokio::test]
+async fn test_bonus_claimed_off_master_chain() {
+    let (validator, app_id, master_chain, _owner) = token_chain().await;
+    let player_chain = validator.new_chain().await;
+    let player = AccountOwner::from(player_chain.public_key());
+
+    player_chain
+        .add_block(|block| {
+            block.with_operation(app_id, TokenOperation::ClaimBonus);
+        })
+        .await;
+    let balance_query = format!("query {{ balance(owner: \"{player}\") }}");
+    let response = query(&player_chain, app_id, &balance_query).await;
+    assert_eq!(amount_of(&response["balance"]), Amount::ZERO);
+
+    master_chain.handle_received_messages().await;
+    let response = query(&master_chain, app_id, "query { issued }").await;
+    assert_eq!(amount_of(&response["issued"]), Amount::from_tokens(11));
+
+    player_chain.handle_received_messages().await;
+    let response = query(&player_chain, app_id, &balance_query).await;
+    assert_eq!(amount_of(&response["balance"]), Amount::from_tokens(1));
+}
+
+/// Tokens moving between chains, burned, overdrawn or paid as bonuses never leave a chain
+/// holding more than its supply.
+#[tokio::test]
+async fn test_held_tokens_stay_within_supply() {
+    let (validator, app_id, master_chain, owner) = token_chain().await;
+    let player_chain = validator.new_chain().await;
+    let player = AccountOwner::from(player_chain.public_key());
+    assert_held(&master_chain, app_id, 10).await;
+
+    let target_account = Account { chain_id: player_chain.id(), owner: player };
+    master_chain
+        .add_block(|block| {
+            block.with_operation(
+                app_id,
+                TokenOperation::Transfer { owner, amount: Amount::from_tokens(4), target_account },
+            );
+        })
+        .await;
+    assert_held(&master_chain, app_id, 6).await;
+    assert_held(&player_chain, app_id, 0).await;
+    player_chain.handle_received_messages().await;
+    assert_held(&player_chain, app_id, 4).await;
+
+    let result = player_chain.try_add_block(|block| {
+        block.with_operation(
+            app_id,
+            TokenOperation::Transfer { owner: player, amount: Amount::from_tokens(5), target_account },
+        );
+    });
+    assert!(result.await.is_err());
+    player_chain
+        .add_block(|block| {
+            block.with_operation(app_id, TokenOperation::Burn { owner: player, amount: Amount::from_tokens(1) });
+            block.with_operation(app_id, TokenOperation::ClaimBonus);
+        })
+        .await;
+    assert_held(&player_chain, app_id, 3).await;
+
+    master_chain.handle_received_messages().await;
+    assert_held(&master_chain, app_id, 6).await;
+    player_chain.handle_received_messages().await;
+    assert_held(&player_chain, app_id, 4).await;
+}
@@ -36,7 +36,7 @@ echo "Bytecode ID: $BYTECODE_ID"
 
 echo "Creating Application..."
 # Using "null" for instantiation argument
-APP_ID=$(linera create-application "$BYTECODE_ID" --json-argument "null" | grep "Application ID:" | awk '{print $3}')
+APP_ID=$(linera create-application "$BYTECODE_ID" --json-parameters '{"admins": []}' --json-argument "null" | grep "Application ID:" | awk '{print $3}')
 echo "Application ID: $APP_ID"
 
 # Update Frontend Configuration
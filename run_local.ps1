@@ -72,7 +72,7 @@ if (-not $BytecodeId) {
 Write-Host "Module ID: $BytecodeId"
 
 Write-Host "Creating Application..."
-$AppOutput = linera create-application $BytecodeId --json-argument "null" --storage memory | Out-String
+$AppOutput = linera create-application $BytecodeId --json-parameters '{"admins": []}' --json-argument "null" --storage memory | Out-String
 $AppId = $AppOutput | Select-String "Application ID: ([a-f0-9]+)" | ForEach-Object { $_.Matches.Groups[1].Value }
 
 if (-not $AppId) {